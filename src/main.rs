@@ -1,15 +1,112 @@
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(bound(deserialize = "'de: 'a"))]
-struct ProductsJson<'a> {
-    products: Vec<models::ProductInfo<'a>>,
-    stores: Vec<models::Store>
+/// Share of the current catalog's products a refresh may lose before it is
+/// treated as a broken scrape.
+const MAX_PRODUCT_DROP: f64 = 0.2;
+
+/// Opens the local catalog database and brings it up to date with the
+/// active source: `--catalog`, else a refreshed catalog, else the bundled one.
+async fn open_catalog(explicit: Option<&Path>) -> catalog::Catalog {
+    let source = catalog::CatalogSource::resolve(explicit);
+    // An explicit file is loaded on its own, so looking at an old products.json
+    // doesn't add to the shared database's refresh and discontinued history.
+    let catalog = if let catalog::CatalogSource::File(_) = source {
+        catalog::Catalog::in_memory().await.unwrap_or_else(|e| {
+            eprintln!("Could not create an in-memory catalog: {}", e);
+            process::exit(1);
+        })
+    } else {
+        let path = catalog::default_database_path();
+        catalog::Catalog::open(&path).await.unwrap_or_else(|e| {
+            eprintln!("Could not open the catalog database at {}: {}", path.display(), e);
+            process::exit(1);
+        })
+    };
+    let bytes = source.read().unwrap_or_else(|e| {
+        eprintln!("Could not read the catalog from {}: {}", source, e);
+        process::exit(1);
+    });
+    if let Err(e) = catalog.sync(&bytes).await {
+        eprintln!("Could not load the catalog from {}: {}", source, e);
+        process::exit(1);
+    }
+    catalog
+}
+
+fn read_file(path: &Path) -> Vec<u8> {
+    std::fs::read(path).unwrap_or_else(|e| {
+        eprintln!("Could not read {}: {}", path.display(), e);
+        process::exit(1);
+    })
+}
+
+fn parse_products_json<'a>(path: &Path, bytes: &'a [u8]) -> catalog::ProductsJson<'a> {
+    serde_json::from_slice(bytes).unwrap_or_else(|e| {
+        eprintln!("{} is not a valid products.json: {}", path.display(), e);
+        process::exit(1);
+    })
 }
 
-/// Returns the bundled product list as Vec<ProductInfo>
-fn bundled_products() -> ProductsJson<'static> {
-    let bytes = include_bytes!("./resources/products.json");
-    serde_json::from_slice(bytes).expect("Failed to parse bundled products.json")
+async fn catalog_products(catalog: &catalog::Catalog, filter: &search::ProductFilter) -> Vec<catalog::ProductRow> {
+    catalog.products_matching(filter).await.unwrap_or_else(|e| {
+        eprintln!("Could not read products from the catalog: {}", e);
+        process::exit(1);
+    })
 }
+
+fn format_date(when: chrono::DateTime<chrono::Utc>) -> String {
+    when.format("%Y-%m-%d").to_string()
+}
+
+/// Checks every BOM line's SKU against the catalog, printing an error with
+/// substitution suggestions for each one that is missing or discontinued.
+/// Returns whether every SKU is listed.
+async fn validate_parts(catalog: &catalog::Catalog, parts: &[Part], unit: units::Unit) -> bool {
+    let mut checked = std::collections::HashSet::new();
+    let mut valid = true;
+    for part in parts {
+        if !checked.insert(part.sku.as_str()) || !catalog.products_by_sku(&part.sku).await.unwrap_or_default().is_empty() {
+            continue;
+        }
+        valid = false;
+        let known = catalog.last_known_product(&part.sku).await.ok().flatten();
+        match catalog.sku_history(&part.sku).await.ok().flatten() {
+            Some(history) => {
+                let days = (chrono::Utc::now() - history.last_seen).num_days();
+                eprintln!(
+                    "Error: SKU {} is no longer in the catalog (last seen {}, {} days ago).",
+                    part.sku,
+                    format_date(history.last_seen),
+                    days
+                );
+            }
+            None => eprintln!("Error: SKU {} is not in the catalog.", part.sku),
+        }
+        // A SKU the catalog never listed can still be described from the
+        // BOM's product ID and qualifiers.
+        let wanted = match known {
+            Some(row) => Some(row),
+            None => catalog.products_by_id(&part.id).await.unwrap_or_default().into_iter().next().map(|row| catalog::ProductRow {
+                sku: part.sku.clone(),
+                qualifier_a: part.qualifier_a.clone(),
+                qualifier_b: part.qualifier_b.clone(),
+                qualifier_c: part.qualifier_c.clone(),
+                ..row
+            }),
+        };
+        let Some(wanted) = wanted else {
+            continue;
+        };
+        let suggestions = catalog.substitutes(&wanted.as_product_info()).await.unwrap_or_default();
+        if !suggestions.is_empty() {
+            eprintln!("  Possible substitutes for {} ({}):", wanted.description, format_size(&wanted.as_product_info(), unit));
+            for (row, kind) in &suggestions {
+                let product = row.as_product_info();
+                eprintln!("    {: <13} {: <35} || SKU: {: <19} | ID: {} | Size: {}", kind.to_string(), product.description, product.sku, product.id, format_size(&product, unit));
+            }
+        }
+    }
+    valid
+}
+
 struct ChromedriverGuard {
     child: std::process::Child,
 }
@@ -21,17 +118,18 @@ impl Drop for ChromedriverGuard {
 }
 
 
-use msrs::metalsupermarkets::models;
+use msrs::metalsupermarkets::{catalog, cost_model, design, diff, models, pricing, scraper, search, section, split, units, weight};
 use reqwest::cookie::CookieStore;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::fs::File;
+use std::path::{Path, PathBuf};
 use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
 use std::process;
-use msrs::metalsupermarkets::api_client::{ApiClient, AddToCartParams};
+use msrs::metalsupermarkets::api_client::{ApiClient, AddToCartParams, GetProductPriceParams};
 use fantoccini::{ClientBuilder, cookies::Cookie};
 use std::process::{Command, Stdio};
-use serde_json;
 use clap::Parser;
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -46,6 +144,13 @@ struct SerializableCookie {
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    /// Use this products.json instead of the refreshed or bundled catalog; it is
+    /// loaded on its own and not recorded in the catalog history
+    #[arg(long, global = true)]
+    catalog: Option<PathBuf>,
+    /// Units for displayed sizes, size filters and BOM lengths without a Units column (in, ft, mm, cm, m)
+    #[arg(long, global = true, default_value_t = units::Unit::Inches)]
+    units: units::Unit,
     #[command(subcommand)]
     command: Commands,
 }
@@ -62,21 +167,269 @@ enum Commands {
         password: String,
         #[arg(short, long, default_value = "023001")]
         store_id: String,
+        /// Order even if some SKUs are missing from the catalog
+        #[arg(long)]
+        no_validate: bool,
+    },
+    /// Price a BOM at a store without adding anything to the cart
+    Quote {
+        #[arg(short, long)]
+        input: String,
+        #[arg(short, long, default_value = "023001")]
+        store: String,
+        /// Price the BOM at each of these stores (comma-separated IDs) and compare
+        #[arg(long, value_delimiter = ',', conflicts_with = "store")]
+        compare_stores: Vec<String>,
+        /// With --compare-stores, extra cost per store used when splitting the order, so it isn't split to save pennies
+        #[arg(long, default_value_t = 0.0, requires = "compare_stores")]
+        pickup_penalty: f64,
+        /// With --compare-stores, write the split order as one BOM per store (<store id>.csv) into this directory
+        #[arg(long, requires = "compare_stores")]
+        split_dir: Option<PathBuf>,
+        /// Quote even if some SKUs are missing from the catalog
+        #[arg(long)]
+        no_validate: bool,
+        /// Ask the store for every price instead of using cached ones (fresh prices are still cached)
+        #[arg(long, conflicts_with = "offline")]
+        no_cache: bool,
+        /// Use cached prices fetched within this many hours
+        #[arg(long, default_value_t = pricing::DEFAULT_CACHE_TTL.num_hours(), conflicts_with = "offline")]
+        cache_ttl_hours: i64,
+        /// Use cached prices, else estimate from the cost models fitted by `prices model`, without contacting the store
+        #[arg(long)]
+        offline: bool,
+        /// With --offline, flag estimates from cost models fitted more than this many days ago
+        #[arg(long, default_value_t = cost_model::DEFAULT_MAX_AGE.num_days(), requires = "offline")]
+        model_max_age_days: i64,
     },
     /// Products scraping commands
     Products {
         #[command(subcommand)]
-        subcmd: Subcommand,
+        subcmd: ProductsSubcommand,
     },
     Stores {
         #[command(subcommand)]
         subcmd: Subcommand,
     },
+    /// Manage the local product catalog
+    Catalog {
+        #[command(subcommand)]
+        subcmd: CatalogSubcommand,
+    },
+    /// Pick stocked products for structural use
+    Design {
+        #[command(subcommand)]
+        subcmd: DesignSubcommand,
+    },
+    /// Look at prices recorded by quote
+    Prices {
+        #[command(subcommand)]
+        subcmd: PricesSubcommand,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum PricesSubcommand {
+    /// Show how a SKU's price per foot (or square foot) has moved over time
+    History {
+        sku: String,
+        /// Only prices from this store
+        #[arg(short, long)]
+        store: Option<String>,
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Probe a SKU's price over a grid of lengths and quantities and fit a cost model to it
+    Model {
+        sku: String,
+        #[arg(short, long, default_value = "023001")]
+        store: String,
+        /// Cut lengths to price (comma-separated)
+        #[arg(long, value_delimiter = ',', default_value = "12in,24in,48in,96in")]
+        lengths: Vec<String>,
+        /// Quantities to price each length at (comma-separated); quantity breaks are only found at these, so sample closely around a suspected break
+        #[arg(long, value_delimiter = ',', default_value = "1,5,10,25")]
+        quantities: Vec<u32>,
+        /// Cut width for sheet and plate
+        #[arg(long, default_value = "12in")]
+        width: String,
+        /// Ask the store for every price instead of using cached ones
+        #[arg(long)]
+        no_cache: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum DesignSubcommand {
+    /// List stocked products that carry a single-span load within stress and deflection limits, lightest or cheapest first
+    Beam {
+        /// Span, e.g. "10ft", "3' 6\"" or "1200mm" (bare numbers are in --units)
+        #[arg(long)]
+        span: String,
+        /// Total load, e.g. "500", "500lb", "200kg" or "2kN" (bare numbers are pounds)
+        #[arg(long)]
+        load: String,
+        /// point (at midspan, or the free end of a cantilever) or uniform
+        #[arg(long, default_value = "uniform")]
+        load_type: design::LoadKind,
+        /// simple, cantilever or fixed
+        #[arg(long, default_value = "simple")]
+        support: design::Support,
+        /// Maximum deflection as a span ratio, e.g. 360 for span / 360
+        #[arg(long, default_value_t = 360.0)]
+        deflection_limit: f64,
+        /// Allowable bending stress is the yield strength divided by this
+        #[arg(long, default_value_t = 1.67)]
+        safety_factor: f64,
+        /// Ignore the member's own weight
+        #[arg(long)]
+        no_self_weight: bool,
+        #[arg(long)]
+        metal: Option<models::Metal>,
+        #[arg(long)]
+        shape: Option<models::Shape>,
+        #[arg(long)]
+        grade: Option<String>,
+        /// Order by weight, or by the price of one piece cut to the span at --store
+        #[arg(long, value_enum, default_value_t = BeamSort::Weight)]
+        sort: BeamSort,
+        /// Show prices at this store, from cached prices or cost models (never asks the store)
+        #[arg(short, long, required_if_eq("sort", "price"))]
+        store: Option<String>,
+        /// Maximum number of results to show
+        #[arg(short, long, default_value_t = 10)]
+        limit: usize,
+    },
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum BeamSort {
+    /// Lightest first
+    Weight,
+    /// Cheapest first; products without a known price come last
+    Price,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum CatalogSubcommand {
+    /// Scrape the website for the current catalog and use it from now on
+    Refresh {
+        /// Where to save products.json (defaults to the per-user cache directory)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Save the scrape even if pages failed, it found no stores or it lost many products
+        #[arg(long)]
+        force: bool,
+    },
+    /// Compare two products.json snapshots
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+        #[arg(short, long, value_enum, default_value_t = DiffFormat::Text)]
+        format: DiffFormat,
+    },
+    /// Show where the active catalog came from, when it was scraped and what it contains
+    Info {
+        /// Warn when the catalog was scraped more than this many days ago
+        #[arg(long, default_value_t = 90)]
+        max_age_days: i64,
+    },
+    /// Show when a SKU was first and last seen, or list recent imports and discontinued SKUs
+    History {
+        sku: Option<String>,
+    },
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffFormat {
+    Text,
+    Json,
 }
 
 #[derive(clap::Subcommand, Debug)]
 enum Subcommand {
-    List,
+    List {
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human readable, padded columns
+    Table,
+    /// A single pretty-printed JSON array
+    Json,
+    /// CSV with a header row
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum ProductsSubcommand {
+    /// List products, optionally filtered by metal, shape, grade and size
+    List {
+        /// Metal family, e.g. "aluminum" or "stainless steel"
+        #[arg(long)]
+        metal: Option<models::Metal>,
+        /// Shape, e.g. "round tube" or "flat bar"
+        #[arg(long)]
+        shape: Option<models::Shape>,
+        /// Grade or alloy, e.g. "6061" or "304"
+        #[arg(long)]
+        grade: Option<String>,
+        /// Minimum first dimension in --units (thickness, diameter, OD, leg, ...)
+        #[arg(long)]
+        min_a: Option<f64>,
+        #[arg(long)]
+        max_a: Option<f64>,
+        /// Minimum second dimension in --units (width, wall, ...)
+        #[arg(long)]
+        min_b: Option<f64>,
+        #[arg(long)]
+        max_b: Option<f64>,
+        /// Minimum third dimension in --units (wall, thickness, ...)
+        #[arg(long)]
+        min_c: Option<f64>,
+        #[arg(long)]
+        max_c: Option<f64>,
+        #[arg(short, long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+    /// Show every catalog entry for a SKU or product ID
+    Show {
+        sku_or_id: String,
+    },
+    /// Find the closest stocked sizes at least as large as the given dimensions
+    Nearest {
+        /// Shape, e.g. "flat bar"
+        #[arg(long)]
+        shape: models::Shape,
+        #[arg(long)]
+        metal: Option<models::Metal>,
+        #[arg(long)]
+        grade: Option<String>,
+        /// Minimum dimensions in --units, in catalog order (e.g. thickness then width for flat bar)
+        #[arg(required = true, num_args = 1..=3)]
+        dimensions: Vec<f64>,
+        /// Maximum number of results to show
+        #[arg(short, long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Show area, moments of inertia, section moduli and radii of gyration for a SKU
+    Props {
+        sku: String,
+    },
+    /// Fuzzy search by description, SKU and size, e.g. "6061 angle 1.5 x 1.5 x .125"
+    Search {
+        /// Search terms; naming a metal or shape only searches that metal or shape
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
+        /// Maximum number of results to show
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -86,8 +439,11 @@ struct Part {
     qualifier_a: String,
     qualifier_b: String,
     qualifier_c: String,
-    length: Option<String>,
-    width: Option<String>,
+    length: Option<units::Length>,
+    width: Option<units::Length>,
+    /// Unit the BOM gave `length` and `width` in, kept when writing BOMs
+    /// back out. Prices are always requested in inches.
+    units: units::Unit,
     quantity: usize,
 }
 
@@ -99,7 +455,8 @@ impl PartialEq for Part {
         self.qualifier_b == other.qualifier_b &&
         self.qualifier_c == other.qualifier_c &&
         self.length == other.length &&
-        self.width == other.width
+        self.width == other.width &&
+        self.units == other.units
     }
 }
 impl Eq for Part {}
@@ -110,8 +467,9 @@ impl Hash for Part {
         self.qualifier_a.hash(state);
         self.qualifier_b.hash(state);
         self.qualifier_c.hash(state);
-        self.length.hash(state);
-        self.width.hash(state);
+        self.length.map(|l| l.inches().to_bits()).hash(state);
+        self.width.map(|w| w.inches().to_bits()).hash(state);
+        self.units.hash(state);
     }
 }
 async fn open_cart_with_cookies(cookies: Vec<SerializableCookie>) -> Result<(), fantoccini::error::CmdError> {
@@ -166,42 +524,733 @@ async fn open_cart_with_cookies(cookies: Vec<SerializableCookie>) -> Result<(),
 
 
 
+/// Four significant figures, without switching to exponent notation.
+fn format_significant(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let decimals = (3 - value.abs().log10().floor() as i32).max(0) as usize;
+    format!("{:.*}", decimals, value)
+}
+
+/// Pounds, or kilograms when displaying metric units.
+fn format_weight(pounds: f64, unit: units::Unit) -> String {
+    if unit.is_metric() {
+        format!("{:.2} kg", pounds * weight::KG_PER_LB)
+    } else {
+        format!("{:.2} lb", pounds)
+    }
+}
+
+fn format_weight_rate(rate: weight::WeightRate, unit: units::Unit) -> String {
+    match (rate, unit.is_metric()) {
+        (weight::WeightRate::PerFoot(lb), false) => format!("{:.3} lb/ft", lb),
+        (weight::WeightRate::PerFoot(lb), true) => format!("{:.3} kg/m", lb * weight::KG_PER_LB / 0.3048),
+        (weight::WeightRate::PerSquareFoot(lb), false) => format!("{:.3} lb/ft²", lb),
+        (weight::WeightRate::PerSquareFoot(lb), true) => format!("{:.3} kg/m²", lb * weight::KG_PER_LB / 0.092_903_04),
+    }
+}
+
+/// Reads a BOM CSV into parts, merging identical rows into one line with a
+/// quantity. Lengths without a Units column are read as `default_units`.
+fn read_bom(input: &str, default_units: units::Unit) -> Vec<Part> {
+    let file = File::open(input).unwrap_or_else(|_| {
+        eprintln!("Could not open the input file. Please check the path and try again.");
+        process::exit(1);
+    });
+    let mut rdr = csv::Reader::from_reader(file);
+    let headers = rdr.headers().expect("Failed to read headers").clone();
+    let required = ["ID", "SKU", "Qualifier A", "Qualifier B", "Qualifier C", "Length", "Width"];
+    for &col in &required {
+        if !headers.iter().any(|h| h == col) {
+            eprintln!("Input file is missing a required column: '{}'.", col);
+            process::exit(1);
+        }
+    }
+    // Optional: a per-row unit for Length and Width, overriding --units.
+    let units_column = headers.iter().position(|h| h == "Units");
+    // Repeated rows become one part with a quantity, kept in first-seen order.
+    let mut parts: Vec<Part> = Vec::new();
+    let mut index: HashMap<Part, usize> = HashMap::new();
+    for (row, result) in rdr.records().enumerate() {
+        let record = result.expect("Failed to read record");
+        let get = |col: &str| record.get(headers.iter().position(|h| h == col).unwrap()).unwrap_or("");
+        let units = match units_column.and_then(|i| record.get(i)).map(str::trim) {
+            None | Some("") => default_units,
+            Some(units) => units.parse().unwrap_or_else(|e| {
+                eprintln!("Row {}: {}.", row + 2, e);
+                process::exit(1);
+            }),
+        };
+        let parse_length = |col: &str| match get(col).trim() {
+            "" => None,
+            s => Some(units::Length::parse(s, units).unwrap_or_else(|e| {
+                eprintln!("Row {}, {}: {}.", row + 2, col, e);
+                process::exit(1);
+            })),
+        };
+        let part = Part {
+            id: get("ID").to_string(),
+            sku: get("SKU").to_string(),
+            qualifier_a: get("Qualifier A").to_string(),
+            qualifier_b: get("Qualifier B").to_string(),
+            qualifier_c: get("Qualifier C").to_string(),
+            length: parse_length("Length"),
+            width: parse_length("Width"),
+            units,
+            quantity: 1,
+        };
+        match index.get(&part) {
+            Some(&i) => parts[i].quantity += 1,
+            None => {
+                index.insert(part.clone(), parts.len());
+                parts.push(part);
+            }
+        }
+    }
+    parts
+}
+
+/// A BOM line's price at one store, or why it has none.
+struct PricedLine {
+    quote: Result<pricing::PriceQuote, String>,
+    /// Came from the price cache rather than the store.
+    cached: bool,
+    /// Estimated offline from a cost model older than the allowed age.
+    stale: bool,
+    /// Estimated offline for a size outside the ones the cost model was
+    /// fitted to.
+    extrapolated: bool,
+}
+
+/// Prices BOM lines through the price cache, recording fresh prices in the
+/// price history, or offline from fitted cost models.
+struct LinePricer {
+    api: ApiClient,
+    cache: pricing::PriceCache,
+    history: pricing::PriceHistory,
+    ttl: chrono::Duration,
+    no_cache: bool,
+    /// Use cached prices, else estimate from cost models, instead of asking
+    /// the store, flagging models older than this.
+    offline: Option<chrono::Duration>,
+}
+
+impl LinePricer {
+    async fn open(no_cache: bool, ttl: chrono::Duration, offline: Option<chrono::Duration>) -> LinePricer {
+        let cache_path = pricing::default_cache_path();
+        let cache = pricing::PriceCache::open(&cache_path).await.unwrap_or_else(|e| {
+            eprintln!("Could not open the price cache at {}: {}", cache_path.display(), e);
+            process::exit(1);
+        });
+        let history_path = pricing::default_history_path();
+        let history = pricing::PriceHistory::open(&history_path).await.unwrap_or_else(|e| {
+            eprintln!("Could not open the price history at {}: {}", history_path.display(), e);
+            process::exit(1);
+        });
+        LinePricer { api: ApiClient::new(), cache, history, ttl, no_cache, offline }
+    }
+
+    async fn price(&self, store: &str, part: &Part) -> PricedLine {
+        if let Some(max_age) = self.offline {
+            return self.lookup(store, part, max_age).await;
+        }
+        let (length, width, quantity) = site_fields(part);
+        let params = price_params(store, part, &length, width.as_deref(), &quantity);
+        if !self.no_cache
+            && let Some(quote) = self.cached(&params).await
+        {
+            return PricedLine { quote: Ok(quote), cached: true, stale: false, extrapolated: false };
+        }
+        let quote = self.history.fetch(&self.api, &params).await.map(|fetched| {
+            if let Some(e) = fetched.record_error {
+                eprintln!("Warning: could not record the price of {} in the price history: {}", part.sku, e);
+            }
+            fetched.quote
+        });
+        if let Ok(quote) = &quote
+            && let Err(e) = self.cache.put(&params, quote).await
+        {
+            eprintln!("Warning: could not cache the price of {}: {}", part.sku, e);
+        }
+        PricedLine { quote: quote.map_err(|e| e.to_string()), cached: false, stale: false, extrapolated: false }
+    }
+
+    /// The cached price for `params`, if any. A cache that can't be read is
+    /// reported and treated as empty.
+    async fn cached(&self, params: &GetProductPriceParams<'_>) -> Option<pricing::PriceQuote> {
+        self.cache.get(params, self.ttl).await.unwrap_or_else(|e| {
+            eprintln!("Warning: could not read the price cache: {}", e);
+            None
+        })
+    }
+
+    /// The line's cached price, else an estimate from its cost model, without
+    /// asking the store.
+    async fn lookup(&self, store: &str, part: &Part, max_age: chrono::Duration) -> PricedLine {
+        let (length, width, quantity) = site_fields(part);
+        let params = price_params(store, part, &length, width.as_deref(), &quantity);
+        if let Some(quote) = self.cached(&params).await {
+            return PricedLine { quote: Ok(quote), cached: true, stale: false, extrapolated: false };
+        }
+        self.estimate(store, part, max_age).await
+    }
+
+    /// Estimates the line from the SKU's cost model at `store`, without any
+    /// network calls.
+    async fn estimate(&self, store: &str, part: &Part, max_age: chrono::Duration) -> PricedLine {
+        let model = match self.history.model(&part.sku, store).await {
+            Ok(model) => model,
+            Err(e) => {
+                let reason = format!("could not read the cost models: {}", e);
+                return PricedLine { quote: Err(reason), cached: false, stale: false, extrapolated: false };
+            }
+        };
+        let Some(model) = model else {
+            let reason = format!("no cost model; fit one with `prices model {} -s {}`", part.sku, store);
+            return PricedLine { quote: Err(reason), cached: false, stale: false, extrapolated: false };
+        };
+        let (length, width) = (part.length.map(|l| l.inches()), part.width.map(|w| w.inches()));
+        let quote = model
+            .estimate(length, width, part.quantity as u32)
+            .ok_or_else(|| "the cost model needs a length and width".to_string());
+        PricedLine { quote, cached: false, stale: model.age() > max_age, extrapolated: model.extrapolates(length, width) }
+    }
+
+    /// Prices every line at `store`, one request at a time.
+    async fn price_all(&self, store: &str, parts: &[Part]) -> Vec<PricedLine> {
+        let mut lines = Vec::with_capacity(parts.len());
+        for part in parts {
+            lines.push(self.price(store, part).await);
+        }
+        lines
+    }
+}
+
+/// A part's length, width and quantity as the site's price fields take them.
+fn site_fields(part: &Part) -> (String, Option<String>, String) {
+    (part.length.map(site_value).unwrap_or_default(), part.width.map(site_value), part.quantity.to_string())
+}
+
+fn price_params<'a>(store: &'a str, part: &'a Part, length: &'a str, width: Option<&'a str>, quantity: &'a str) -> GetProductPriceParams<'a> {
+    GetProductPriceParams {
+        action: pricing::PRICE_ACTION,
+        store_id: store,
+        store_country: "USA",
+        pro_id: &part.id,
+        pro_sku: &part.sku,
+        prowidth: width,
+        prolength: length,
+        selunits: units::SITE_UNITS,
+        selquantity: quantity,
+    }
+}
+
+/// Prints each BOM line's price at every store side by side, with the
+/// cheapest store per line and for the whole BOM.
+fn print_store_comparison(parts: &[Part], store_ids: &[String], stores: &[models::Store], quotes: &[Vec<PricedLine>]) {
+    let line_price = |store: usize, line: usize| quotes[store][line].quote.as_ref().ok();
+    println!("Quote comparison:");
+    println!("{}", "-".repeat(60));
+    for id in store_ids {
+        let name = stores.iter().find(|s| &s.id == id).map(|s| s.name.as_str()).unwrap_or("unknown store");
+        println!("{: <8} {}", id, name);
+    }
+    println!();
+    let mut header = format!("{: <35} ||", "Line");
+    for id in store_ids {
+        header.push_str(&format!(" {: >12} |", id));
+    }
+    header.push_str(" Cheapest");
+    println!("{}", header);
+    for (i, part) in parts.iter().enumerate() {
+        let label = format!(
+            "{} x{} {}",
+            part.sku,
+            part.quantity,
+            part.length.map(|l| l.to_string()).unwrap_or_default()
+        );
+        let mut row = format!("{: <35} ||", label);
+        for lines in quotes {
+            let priced = &lines[i];
+            let mut cell = priced
+                .quote
+                .as_ref()
+                .map(|q| pricing::format_money(q.extended_price, &q.currency))
+                .unwrap_or_else(|_| "-".to_string());
+            if priced.stale {
+                cell.push('!');
+            }
+            if priced.extrapolated {
+                cell.push('~');
+            }
+            row.push_str(&format!(" {: >12} |", cell));
+        }
+        let cheapest = (0..store_ids.len())
+            .filter_map(|s| Some((s, line_price(s, i)?.extended_price)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        row.push_str(&format!(" {}", cheapest.map(|(s, _)| store_ids[s].as_str()).unwrap_or("none")));
+        println!("{}", row);
+    }
+    let mut row = format!("{: <35} ||", "Total");
+    let mut complete = Vec::new();
+    for (s, lines) in quotes.iter().enumerate() {
+        let unpriced = lines.iter().filter(|l| l.quote.is_err()).count();
+        let total: f64 = lines.iter().filter_map(|l| l.quote.as_ref().ok()).map(|q| q.extended_price).sum();
+        let currency = lines.iter().find_map(|l| l.quote.as_ref().ok()).map_or("USD", |q| q.currency.as_str());
+        let mut cell = pricing::format_money(total, currency);
+        if unpriced > 0 {
+            cell.push('*');
+        } else {
+            complete.push((s, total, currency));
+        }
+        row.push_str(&format!(" {: >12} |", cell));
+    }
+    println!("{}", row);
+    if complete.len() < store_ids.len() {
+        println!("* excludes lines that store could not price");
+    }
+    if quotes.iter().flatten().any(|l| l.stale) {
+        println!("! estimated from a stale cost model; refit with `prices model`");
+    }
+    if quotes.iter().flatten().any(|l| l.extrapolated) {
+        println!("~ outside the lengths the cost model was fitted to; refit with `prices model --lengths` covering it");
+    }
+    match complete.iter().min_by(|a, b| a.1.total_cmp(&b.1)) {
+        Some(&(s, total, currency)) => {
+            println!("Cheapest overall: store {} at {}", store_ids[s], pricing::format_money(total, currency))
+        }
+        None => println!("No store priced every line, so there is no overall cheapest store."),
+    }
+    let errors: Vec<String> = quotes
+        .iter()
+        .enumerate()
+        .flat_map(|(s, lines)| {
+            lines.iter().zip(parts).filter_map(move |(line, part)| {
+                let e = line.quote.as_ref().err()?;
+                Some(format!("  {} at {}: {}", part.sku, store_ids[s], e))
+            })
+        })
+        .collect();
+    if !errors.is_empty() {
+        println!("Could not price:");
+        for e in errors {
+            println!("{}", e);
+        }
+    }
+    let cached = quotes.iter().flatten().filter(|l| l.cached).count();
+    if cached > 0 {
+        println!("{} of {} price(s) came from the cache; pass --no-cache to ask the stores again.", cached, parts.len() * store_ids.len());
+    }
+}
+
+/// Prints which store each line goes to under `plan`, with its cost next to
+/// buying everything at the cheapest single store.
+fn print_split_plan(plan: &split::SplitPlan, parts: &[Part], store_ids: &[String], quotes: &[Vec<PricedLine>], pickup_penalty: f64) {
+    let currency = quotes.iter().flatten().find_map(|l| l.quote.as_ref().ok()).map_or("USD", |q| q.currency.as_str());
+    println!("Split plan (pickup penalty {} per store):", pricing::format_money(pickup_penalty, currency));
+    println!("{}", "-".repeat(60));
+    for s in plan.stores() {
+        let lines = plan.lines_at(s);
+        let cost: f64 = lines.iter().filter_map(|&line| quotes[s][line].quote.as_ref().ok()).map(|q| q.extended_price).sum();
+        let skus: Vec<&str> = lines.iter().map(|&line| parts[line].sku.as_str()).collect();
+        println!("{: <8} || {} line(s) | {} | {}", store_ids[s], lines.len(), pricing::format_money(cost, currency), skus.join(", "));
+    }
+    println!(
+        "Total: {} + {} pickup = {}",
+        pricing::format_money(plan.material_cost, currency),
+        pricing::format_money(plan.pickup_cost, currency),
+        pricing::format_money(plan.total(), currency)
+    );
+    let single = quotes
+        .iter()
+        .enumerate()
+        .filter(|(_, lines)| lines.iter().all(|l| l.quote.is_ok()))
+        .map(|(s, lines)| (s, lines.iter().filter_map(|l| l.quote.as_ref().ok()).map(|q| q.extended_price).sum::<f64>() + pickup_penalty))
+        .min_by(|a, b| a.1.total_cmp(&b.1));
+    if let Some((s, cost)) = single
+        && plan.stores().len() > 1
+    {
+        println!(
+            "Everything at {}: {} including pickup, so splitting saves {}",
+            store_ids[s],
+            pricing::format_money(cost, currency),
+            pricing::format_money(cost - plan.total(), currency)
+        );
+    }
+    let unassigned: Vec<&str> = (0..parts.len())
+        .filter(|&line| plan.assignments[line].is_none())
+        .map(|line| parts[line].sku.as_str())
+        .collect();
+    if !unassigned.is_empty() {
+        println!("Not in any store's order (no store priced them): {}", unassigned.join(", "));
+    }
+}
+
+/// Writes `parts` as a BOM `order` can read: one row per piece, with each
+/// line's lengths in its own units.
+fn write_bom(path: &Path, parts: &[&Part]) -> Result<(), csv::Error> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(["ID", "SKU", "Qualifier A", "Qualifier B", "Qualifier C", "Length", "Width", "Units"])?;
+    for part in parts {
+        let length = part.length.map(|l| rounded_value(l, part.units)).unwrap_or_default();
+        let width = part.width.map(|w| rounded_value(w, part.units)).unwrap_or_default();
+        for _ in 0..part.quantity {
+            wtr.write_record([
+                &part.id,
+                &part.sku,
+                &part.qualifier_a,
+                &part.qualifier_b,
+                &part.qualifier_c,
+                &length,
+                &width,
+                part.units.symbol(),
+            ])?;
+        }
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Prints each BOM line with its weight, and the order's total weight.
+async fn print_order_summary(catalog: &catalog::Catalog, parts: &[Part], unit: units::Unit) {
+    println!("Order summary:");
+    println!("{}", "-".repeat(60));
+    let mut total = 0.0;
+    let mut unknown = 0;
+    for part in parts {
+        let row = catalog.products_by_sku(&part.sku).await.unwrap_or_default().into_iter().next();
+        let rate = row.as_ref().and_then(|r| weight::weight_rate(&r.as_product_info()));
+        let line_weight = rate
+            .zip(part.length)
+            .and_then(|(rate, length)| rate.weight(length, part.width))
+            .map(|w| w * part.quantity as f64);
+        match line_weight {
+            Some(w) => total += w,
+            None => unknown += 1,
+        }
+        println!(
+            "{: <19} || Qty: {: <3} | Length: {: <10} | {: <14} | Weight: {}",
+            part.sku,
+            part.quantity,
+            part.length.map(|l| l.to_string()).unwrap_or_default(),
+            rate.map(|r| format_weight_rate(r, unit)).unwrap_or_default(),
+            line_weight.map(|w| format_weight(w, unit)).unwrap_or_else(|| "unknown".to_string())
+        );
+    }
+    if unknown > 0 {
+        println!("Total weight: {} (excluding {} line(s) of unknown weight)", format_weight(total, unit), unknown);
+    } else {
+        println!("Total weight: {}", format_weight(total, unit));
+    }
+}
+
+/// A price per foot or square foot, per metre or square metre when displaying metric units.
+fn format_price_rate(rate: pricing::PriceRate, unit: units::Unit, currency: &str) -> String {
+    match (rate, unit.is_metric()) {
+        (pricing::PriceRate::PerFoot(price), false) => format!("{}/ft", pricing::format_money(price, currency)),
+        (pricing::PriceRate::PerFoot(price), true) => format!("{}/m", pricing::format_money(price / 0.3048, currency)),
+        (pricing::PriceRate::PerSquareFoot(price), false) => format!("{}/ft²", pricing::format_money(price, currency)),
+        (pricing::PriceRate::PerSquareFoot(price), true) => format!("{}/m²", pricing::format_money(price / 0.092_903_04, currency)),
+    }
+}
+
+fn print_price_history(out: &mut impl Write, sku: &str, records: &[pricing::PriceRecord], format: OutputFormat, unit: units::Unit) -> io::Result<()> {
+    match format {
+        OutputFormat::Table => {
+            if records.is_empty() {
+                writeln!(out, "No prices recorded for '{}'. Run quote on a BOM that includes it first.", sku)?;
+                return Ok(());
+            }
+            writeln!(out, "Price history for {}:", sku)?;
+            writeln!(out, "{}", "-".repeat(60))?;
+            for record in records {
+                writeln!(
+                    out,
+                    "{} || Store: {: <6} | Length: {: <10} | Qty: {: <3} | Each: {: >10} | {}",
+                    record.recorded_at.format("%Y-%m-%d %H:%M"),
+                    record.store_id,
+                    record.length.map(|l| unit.format(l)).unwrap_or_default(),
+                    record.quantity,
+                    pricing::format_money(record.quote.unit_price, &record.quote.currency),
+                    record.rate().map(|r| format_price_rate(r, unit, &record.quote.currency)).unwrap_or_default()
+                )?;
+            }
+            let mut stores: Vec<&str> = records.iter().map(|r| r.store_id.as_str()).collect();
+            stores.sort();
+            stores.dedup();
+            writeln!(out)?;
+            for store in stores {
+                // Compare like with like: the same rate basis and currency as the latest price.
+                let rated: Vec<(&pricing::PriceRecord, pricing::PriceRate)> = records
+                    .iter()
+                    .filter(|r| r.store_id == store)
+                    .filter_map(|r| Some((r, r.rate()?)))
+                    .collect();
+                let Some(&(last, last_rate)) = rated.last() else { continue };
+                let Some(&(first, first_rate)) = rated.iter().find(|(r, rate)| {
+                    r.quote.currency == last.quote.currency
+                        && std::mem::discriminant(rate) == std::mem::discriminant(&last_rate)
+                }) else { continue };
+                if std::ptr::eq(first, last) {
+                    writeln!(out, "Store {}: one price so far, {}", store, format_price_rate(last_rate, unit, &last.quote.currency))?;
+                    continue;
+                }
+                let change = (last_rate.value() / first_rate.value() - 1.0) * 100.0;
+                writeln!(
+                    out,
+                    "Store {}: {} on {} -> {} on {} ({:+.1}%)",
+                    store,
+                    format_price_rate(first_rate, unit, &first.quote.currency),
+                    format_date(first.recorded_at),
+                    format_price_rate(last_rate, unit, &last.quote.currency),
+                    format_date(last.recorded_at),
+                    change
+                )?;
+            }
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, records)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Ndjson => {
+            for record in records {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(&mut *out);
+            wtr.write_record([
+                "Recorded At", "Store", "SKU", "ID", "Length (in)", "Width (in)", "Quantity",
+                "Unit Price", "Extended Price", "Cut Charge", "Currency", "Rate", "Rate Basis",
+            ])?;
+            let number = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+            for record in records {
+                let rate = record.rate();
+                let basis = match (rate, unit.is_metric()) {
+                    (None, _) => "",
+                    (Some(pricing::PriceRate::PerFoot(_)), false) => "per ft",
+                    (Some(pricing::PriceRate::PerFoot(_)), true) => "per m",
+                    (Some(pricing::PriceRate::PerSquareFoot(_)), false) => "per sq ft",
+                    (Some(pricing::PriceRate::PerSquareFoot(_)), true) => "per sq m",
+                };
+                let rate = rate.map(|rate| match (rate, unit.is_metric()) {
+                    (pricing::PriceRate::PerFoot(price), true) => price / 0.3048,
+                    (pricing::PriceRate::PerSquareFoot(price), true) => price / 0.092_903_04,
+                    (rate, false) => rate.value(),
+                });
+                wtr.write_record([
+                    &record.recorded_at.to_rfc3339(),
+                    &record.store_id,
+                    &record.sku,
+                    &record.product_id,
+                    &number(record.length),
+                    &number(record.width),
+                    &record.quantity.to_string(),
+                    &record.quote.unit_price.to_string(),
+                    &record.quote.extended_price.to_string(),
+                    &number(record.quote.cut_charge),
+                    &record.quote.currency,
+                    &number(rate),
+                    basis,
+                ])?;
+            }
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// `length` in `unit`, rounded to four decimals.
+fn rounded_value(length: units::Length, unit: units::Unit) -> String {
+    let value = (length.to(unit) * 10_000.0).round() / 10_000.0;
+    value.to_string()
+}
+
+/// `length` for the site's cut-length fields, which take inches.
+fn site_value(length: units::Length) -> String {
+    rounded_value(length, units::Unit::Inches)
+}
+
+/// Typed dimensions in `unit` when the shape is understood, otherwise the raw qualifiers.
+fn format_size(product: &models::ProductInfo, unit: units::Unit) -> String {
+    match product.dimensions() {
+        Some(dimensions) => dimensions.to_string_in(unit),
+        None => [product.qualifier_a, product.qualifier_b, product.qualifier_c]
+            .into_iter()
+            .filter(|q| !q.is_empty())
+            .collect::<Vec<_>>()
+            .join(" X "),
+    }
+}
+
+/// Runs `print` against a locked stdout. A closed pipe, as when piping into
+/// `head`, ends the program quietly; other write errors are reported.
+fn write_stdout(print: impl FnOnce(&mut io::StdoutLock<'static>) -> io::Result<()>) {
+    let mut out = io::stdout().lock();
+    if let Err(e) = print(&mut out).and_then(|()| out.flush()) {
+        if e.kind() == io::ErrorKind::BrokenPipe {
+            process::exit(0);
+        }
+        eprintln!("Could not write the output: {}", e);
+        process::exit(1);
+    }
+}
+
+/// A product plus its parsed attributes, as emitted by the JSON formats.
+#[derive(Serialize)]
+struct ProductRecord<'p, 'a> {
+    #[serde(flatten)]
+    product: &'p models::ProductInfo<'a>,
+    material: Option<models::Material>,
+    dimensions: Option<models::Dimensions>,
+}
+
+fn print_products(out: &mut impl Write, products: &[&models::ProductInfo], format: OutputFormat, unit: units::Unit) -> io::Result<()> {
+    let records: Vec<ProductRecord> = products
+        .iter()
+        .map(|product| ProductRecord { product, material: product.material(), dimensions: product.dimensions() })
+        .collect();
+    match format {
+        OutputFormat::Table if products.is_empty() => writeln!(out, "No products matched the given filters")?,
+        OutputFormat::Table => {
+            writeln!(out, "Products:")?;
+            writeln!(out, "{}", "-".repeat(60))?;
+            for product in products {
+                writeln!(out, "{: <35} || SKU: {: <19} | ID: {} | Size: {}", product.description, product.sku, product.id, format_size(product, unit))?;
+            }
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &records)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Ndjson => {
+            for record in &records {
+                serde_json::to_writer(&mut *out, record)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(&mut *out);
+            wtr.write_record([
+                "SKU", "ID", "Description", "Qualifier A", "Qualifier B", "Qualifier C",
+                "Requires Length", "Requires Width", "Metal", "Grade", "Process", "Shape", "Size",
+            ])?;
+            for record in &records {
+                let product = record.product;
+                let material = record.material.as_ref();
+                wtr.write_record([
+                    product.sku,
+                    product.id,
+                    product.description,
+                    product.qualifier_a,
+                    product.qualifier_b,
+                    product.qualifier_c,
+                    &product.requires_length.to_string(),
+                    &product.requires_width.to_string(),
+                    material.map(|m| m.metal.name()).unwrap_or(""),
+                    material.and_then(|m| m.grade.as_deref()).unwrap_or(""),
+                    material.and_then(|m| m.process).map(|p| p.name()).unwrap_or(""),
+                    material.map(|m| m.shape.name()).unwrap_or(""),
+                    &format_size(product, unit),
+                ])?;
+            }
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn print_sku_history(out: &mut impl Write, sku: &str, history: Option<&catalog::SkuHistory>) -> io::Result<()> {
+    let Some(history) = history else {
+        return writeln!(out, "SKU '{}' has never been in the catalog", sku);
+    };
+    writeln!(out, "SKU:        {}", history.sku)?;
+    writeln!(out, "First seen: {}", format_date(history.first_seen))?;
+    writeln!(out, "Last seen:  {}", format_date(history.last_seen))?;
+    match history.discontinued_at {
+        Some(when) => writeln!(out, "Status:     discontinued since {}", format_date(when)),
+        None => writeln!(out, "Status:     listed"),
+    }
+}
+
+fn print_import_history(out: &mut impl Write, refreshes: &[catalog::Refresh], discontinued: &[catalog::SkuHistory]) -> io::Result<()> {
+    writeln!(out, "Imports:")?;
+    writeln!(out, "{}", "-".repeat(60))?;
+    for refresh in refreshes {
+        writeln!(
+            out,
+            "{} || {} products | {} stores | source {}",
+            refresh.recorded_at.format("%Y-%m-%d %H:%M"),
+            refresh.product_count,
+            refresh.store_count,
+            refresh.source_hash
+        )?;
+    }
+    writeln!(out)?;
+    if discontinued.is_empty() {
+        return writeln!(out, "No discontinued SKUs");
+    }
+    writeln!(out, "Discontinued SKUs:")?;
+    writeln!(out, "{}", "-".repeat(60))?;
+    for history in discontinued {
+        writeln!(
+            out,
+            "{: <19} || first seen {} | last seen {}",
+            history.sku,
+            format_date(history.first_seen),
+            format_date(history.last_seen)
+        )?;
+    }
+    Ok(())
+}
+
+fn print_stores(out: &mut impl Write, stores: &[models::Store], format: OutputFormat) -> io::Result<()> {
+    match format {
+        OutputFormat::Table if stores.is_empty() => writeln!(out, "No stores found in the catalog")?,
+        OutputFormat::Table => {
+            writeln!(out, "Stores:")?;
+            writeln!(out, "{}", "-".repeat(60))?;
+            for store in stores {
+                writeln!(out, "{: <30} || ID: {}", store.name, store.id)?;
+            }
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, stores)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Ndjson => {
+            for store in stores {
+                serde_json::to_writer(&mut *out, store)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(&mut *out);
+            wtr.write_record(["ID", "Name"])?;
+            for store in stores {
+                wtr.write_record([&store.id, &store.name])?;
+            }
+            wtr.flush()?;
+        }
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
     match cli.command {
-        Commands::Order { input, username, password, store_id } => {
+        Commands::Order { input, username, password, store_id, no_validate } => {
             // ...existing order logic...
-            let file = File::open(&input).unwrap_or_else(|_| {
-                eprintln!("Could not open the input file. Please check the path and try again.");
+            let deduped = read_bom(&input, cli.units);
+            let catalog = open_catalog(cli.catalog.as_deref()).await;
+            if !no_validate && !validate_parts(&catalog, &deduped, cli.units).await {
+                eprintln!("Fix the SKUs above, or pass --no-validate to order anyway.");
                 process::exit(1);
-            });
-            let mut rdr = csv::Reader::from_reader(file);
-            let headers = rdr.headers().expect("Failed to read headers").clone();
-            let required = ["ID", "SKU", "Qualifier A", "Qualifier B", "Qualifier C", "Length", "Width"];
-            for &col in &required {
-                if !headers.iter().any(|h| h == col) {
-                    eprintln!("Input file is missing a required column: '{}'.", col);
-                    process::exit(1);
-                }
-            }
-            let mut part_map: HashMap<Part, usize> = HashMap::new();
-            for result in rdr.records() {
-                let record = result.expect("Failed to read record");
-                let get = |col: &str| record.get(headers.iter().position(|h| h == col).unwrap()).unwrap_or("");
-                let part = Part {
-                    id: get("ID").to_string(),
-                    sku: get("SKU").to_string(),
-                    qualifier_a: get("Qualifier A").to_string(),
-                    qualifier_b: get("Qualifier B").to_string(),
-                    qualifier_c: get("Qualifier C").to_string(),
-                    length: match get("Length").trim() { "" => None, s => Some(s.to_string()) },
-                    width: match get("Width").trim() { "" => None, s => Some(s.to_string()) },
-                    quantity: 1,
-                };
-                *part_map.entry(part).or_insert(0) += 1;
             }
-            let deduped: Vec<Part> = part_map.into_iter().map(|(mut part, qty)| { part.quantity = qty; part }).collect();
+            print_order_summary(&catalog, &deduped, cli.units).await;
             println!("Logging in...");
             let api = ApiClient::new();
             let login_res = api.login(&username, &password).await;
@@ -221,15 +1270,17 @@ async fn main() {
             }
             for (idx, part) in deduped.iter().enumerate() {
                 println!("Adding item {} of {} to cart...", idx + 1, deduped.len());
+                let length = part.length.map(site_value).unwrap_or_default();
+                let width = part.width.map(site_value);
                 let params = AddToCartParams {
                     action: "put_addtocart",
                     store_id: &store_id,
                     store_country: "USA",
                     pro_id: &part.id,
                     pro_sku: &part.sku,
-                    prowidth: part.width.as_deref(),
-                    prolength: part.length.as_deref().unwrap_or(""),
-                    selunits: "Inches",
+                    prowidth: width.as_deref(),
+                    prolength: &length,
+                    selunits: units::SITE_UNITS,
                     selquantity: &part.quantity.to_string(),
                     pro_price: "0.0",
                 };
@@ -272,37 +1323,661 @@ async fn main() {
                 eprintln!("Failed to launch browser for manual checkout: {}", e);
             }
         }
+        Commands::Quote {
+            input,
+            store,
+            compare_stores,
+            pickup_penalty,
+            split_dir,
+            no_validate,
+            no_cache,
+            cache_ttl_hours,
+            offline,
+            model_max_age_days,
+        } => {
+            let parts = read_bom(&input, cli.units);
+            let catalog = open_catalog(cli.catalog.as_deref()).await;
+            if !no_validate && !validate_parts(&catalog, &parts, cli.units).await {
+                eprintln!("Fix the SKUs above, or pass --no-validate to quote anyway.");
+                process::exit(1);
+            }
+            let max_model_age = offline.then(|| chrono::Duration::days(model_max_age_days));
+            let pricer = LinePricer::open(no_cache, chrono::Duration::hours(cache_ttl_hours), max_model_age).await;
+            if !compare_stores.is_empty() {
+                if compare_stores.len() > split::MAX_STORES {
+                    eprintln!("Compare at most {} stores at a time.", split::MAX_STORES);
+                    process::exit(1);
+                }
+                if !pickup_penalty.is_finite() || pickup_penalty < 0.0 {
+                    eprintln!("--pickup-penalty must be zero or more.");
+                    process::exit(1);
+                }
+                let stores = catalog.stores().await.unwrap_or_else(|e| {
+                    eprintln!("Could not read stores from the catalog: {}", e);
+                    process::exit(1);
+                });
+                if stores.is_empty() {
+                    eprintln!("The catalog lists no stores to check the store IDs against. Run `catalog refresh` to update it.");
+                    process::exit(1);
+                }
+                for id in &compare_stores {
+                    if !stores.iter().any(|s| &s.id == id) {
+                        eprintln!("Store '{}' is not in the catalog's store list. See `stores list`.", id);
+                        process::exit(1);
+                    }
+                }
+                let quotes = futures::future::join_all(compare_stores.iter().map(|id| pricer.price_all(id, &parts))).await;
+                // Totals and the split plan add prices up, which only makes sense in one currency.
+                let mut currencies: Vec<(&str, &str)> = quotes
+                    .iter()
+                    .zip(&compare_stores)
+                    .flat_map(|(lines, id)| lines.iter().filter_map(move |l| Some((l.quote.as_ref().ok()?.currency.as_str(), id.as_str()))))
+                    .collect();
+                currencies.sort();
+                currencies.dedup();
+                if currencies.first().is_some_and(|(first, _)| currencies.iter().any(|(c, _)| c != first)) {
+                    let listed: Vec<String> = currencies.iter().map(|(currency, id)| format!("{} at {}", currency, id)).collect();
+                    eprintln!("Can't compare prices in different currencies ({}). Compare stores that price in the same currency.", listed.join(", "));
+                    process::exit(1);
+                }
+                print_store_comparison(&parts, &compare_stores, &stores, &quotes);
+                let prices: Vec<Vec<Option<f64>>> = (0..parts.len())
+                    .map(|line| quotes.iter().map(|lines| lines[line].quote.as_ref().ok().map(|q| q.extended_price)).collect())
+                    .collect();
+                let plan = split::plan_split(&prices, compare_stores.len(), pickup_penalty);
+                println!();
+                print_split_plan(&plan, &parts, &compare_stores, &quotes, pickup_penalty);
+                if let Some(dir) = split_dir {
+                    std::fs::create_dir_all(&dir).unwrap_or_else(|e| {
+                        eprintln!("Could not create {}: {}", dir.display(), e);
+                        process::exit(1);
+                    });
+                    println!();
+                    for s in plan.stores() {
+                        let path = dir.join(format!("{}.csv", compare_stores[s]));
+                        let lines: Vec<&Part> = plan.lines_at(s).into_iter().map(|line| &parts[line]).collect();
+                        write_bom(&path, &lines).unwrap_or_else(|e| {
+                            eprintln!("Could not write {}: {}", path.display(), e);
+                            process::exit(1);
+                        });
+                        println!("Wrote {} (order with: order -i {} -s {} ...)", path.display(), path.display(), compare_stores[s]);
+                    }
+                }
+                return;
+            }
+            let lines = pricer.price_all(&store, &parts).await;
+            if offline {
+                println!("Estimate for store {} from cached prices and cost models:", store);
+            } else {
+                println!("Quote for store {}:", store);
+            }
+            println!("{}", "-".repeat(60));
+            let mut total = 0.0;
+            let mut unpriced = 0;
+            let mut currency: Option<String> = None;
+            let mut cached = 0;
+            let mut stale = 0;
+            let mut extrapolated = 0;
+            for (part, priced) in parts.iter().zip(lines) {
+                if priced.cached {
+                    cached += 1;
+                }
+                if priced.stale {
+                    stale += 1;
+                }
+                if priced.extrapolated {
+                    extrapolated += 1;
+                }
+                let (each, line) = match priced.quote {
+                    Ok(quote) => {
+                        total += quote.extended_price;
+                        let currency = currency.get_or_insert_with(|| quote.currency.clone());
+                        let mut line = pricing::format_money(quote.extended_price, currency);
+                        if let Some(cut) = quote.cut_charge {
+                            line.push_str(&format!(" (incl. {} cutting)", pricing::format_money(cut, currency)));
+                        }
+                        if priced.stale {
+                            line.push_str(" [stale model]");
+                        }
+                        if priced.extrapolated {
+                            line.push_str(" [extrapolated]");
+                        }
+                        (pricing::format_money(quote.unit_price, currency), line)
+                    }
+                    Err(e) => {
+                        unpriced += 1;
+                        (String::new(), format!("could not price ({})", e))
+                    }
+                };
+                println!(
+                    "{: <19} || Qty: {: <3} | Length: {: <10} | Each: {: >10} | Line: {}",
+                    part.sku,
+                    part.quantity,
+                    part.length.map(|l| l.to_string()).unwrap_or_default(),
+                    each,
+                    line
+                );
+            }
+            let total = pricing::format_money(total, currency.as_deref().unwrap_or("USD"));
+            if unpriced > 0 {
+                println!("Total: {} (excluding {} line(s) that could not be priced)", total, unpriced);
+            } else {
+                println!("Total: {}", total);
+            }
+            if cached > 0 && offline {
+                println!("{} of {} line(s) priced from the cache.", cached, parts.len());
+            } else if cached > 0 {
+                println!("{} of {} line(s) priced from the cache; pass --no-cache to ask the store again.", cached, parts.len());
+            }
+            if stale > 0 {
+                println!(
+                    "{} line(s) estimated from cost models older than {} days; refit them with `prices model` when online.",
+                    stale, model_max_age_days
+                );
+            }
+            if extrapolated > 0 {
+                println!(
+                    "{} line(s) are outside the lengths their cost model was fitted to; refit with `prices model --lengths` covering them.",
+                    extrapolated
+                );
+            }
+        }
         Commands::Products { subcmd } => {
             match subcmd {
-                Subcommand::List => {
-                    let products = bundled_products().products;
-                    if products.is_empty() {
-                        println!("No products found in bundled products");
+                ProductsSubcommand::List { metal, shape, grade, min_a, max_a, min_b, max_b, min_c, max_c, format } => {
+                    let to_inches = |value: Option<f64>| value.map(|v| cli.units.to_inches(v));
+                    let filter = search::ProductFilter {
+                        metal,
+                        shape,
+                        grade,
+                        min_a: to_inches(min_a),
+                        max_a: to_inches(max_a),
+                        min_b: to_inches(min_b),
+                        max_b: to_inches(max_b),
+                        min_c: to_inches(min_c),
+                        max_c: to_inches(max_c),
+                    };
+                    let rows = catalog_products(&open_catalog(cli.catalog.as_deref()).await, &filter).await;
+                    let all_products: Vec<models::ProductInfo> = rows.iter().map(|r| r.as_product_info()).collect();
+                    let products = filter.apply(&all_products);
+                    write_stdout(|out| print_products(out, &products, format, cli.units));
+                }
+                ProductsSubcommand::Show { sku_or_id } => {
+                    let catalog = open_catalog(cli.catalog.as_deref()).await;
+                    let mut rows = catalog.products_by_sku(&sku_or_id).await.unwrap_or_default();
+                    if rows.is_empty() {
+                        rows = catalog.products_by_id(&sku_or_id).await.unwrap_or_default();
+                    }
+                    if rows.is_empty() {
+                        println!("No product with SKU or ID '{}' in the catalog", sku_or_id);
+                    } else {
+                        for row in &rows {
+                            let product = row.as_product_info();
+                            println!("{: <35} || SKU: {: <19} | ID: {} | Size: {}", product.description, product.sku, product.id, format_size(&product, cli.units));
+                        }
+                    }
+                }
+                ProductsSubcommand::Nearest { shape, metal, grade, dimensions, limit } => {
+                    let filter = search::ProductFilter {
+                        metal,
+                        shape: Some(shape),
+                        grade,
+                        min_a: dimensions.first().map(|v| cli.units.to_inches(*v)),
+                        min_b: dimensions.get(1).map(|v| cli.units.to_inches(*v)),
+                        min_c: dimensions.get(2).map(|v| cli.units.to_inches(*v)),
+                        ..Default::default()
+                    };
+                    let rows = catalog_products(&open_catalog(cli.catalog.as_deref()).await, &filter).await;
+                    let products: Vec<models::ProductInfo> = rows.iter().map(|r| r.as_product_info()).collect();
+                    let hits = search::nearest(&products, &filter);
+                    if hits.is_empty() {
+                        println!("No stocked {} is at least that large", shape);
                     } else {
-                        println!("Products:");
+                        println!("Closest stocked sizes:");
                         println!("{}", "-".repeat(60));
-                        for product in &products {
-                            println!("{} || SKU: {} | ID: {} | Size: {} X {}", format!("{: <35}", product.description), format!("{: <19}", product.sku), product.id, product.qualifier_a, product.qualifier_b);
+                        for hit in hits.iter().take(limit) {
+                            let product = hit.product;
+                            println!(
+                                "{: <35} || SKU: {: <19} | ID: {} | Size: {} | Oversize: {:.1}%",
+                                product.description,
+                                product.sku,
+                                product.id,
+                                format_size(product, cli.units),
+                                hit.oversize * 100.0
+                            );
+                        }
+                    }
+                }
+                ProductsSubcommand::Props { sku } => {
+                    let rows = open_catalog(cli.catalog.as_deref()).await.products_by_sku(&sku).await.unwrap_or_default();
+                    let Some(row) = rows.first() else {
+                        println!("No product with SKU '{}' in the catalog", sku);
+                        return;
+                    };
+                    let product = row.as_product_info();
+                    println!("{: <35} || SKU: {: <19} | ID: {} | Size: {}", product.description, product.sku, product.id, format_size(&product, cli.units));
+                    let Some(props) = product.dimensions().as_ref().and_then(section::properties) else {
+                        println!("Section properties are not available for this shape");
+                        return;
+                    };
+                    let unit = cli.units;
+                    let scale = |value: f64, power: i32| format_significant(value * unit.from_inches(1.0).powi(power));
+                    let symbol = unit.symbol();
+                    println!("Area:               {} {}²", scale(props.area, 2), symbol);
+                    println!("Moment of inertia:  Ix {} {}⁴ | Iy {} {}⁴", scale(props.ix, 4), symbol, scale(props.iy, 4), symbol);
+                    println!("Section modulus:    Sx {} {}³ | Sy {} {}³", scale(props.sx, 3), symbol, scale(props.sy, 3), symbol);
+                    println!("Radius of gyration: rx {} {} | ry {} {}", scale(props.rx, 1), symbol, scale(props.ry, 1), symbol);
+                    if let Some(rate) = weight::weight_rate(&product) {
+                        println!("Weight:             {}", format_weight_rate(rate, unit));
+                    }
+                }
+                ProductsSubcommand::Search { query, limit } => {
+                    let filter = search::query_filter(&query.join(" "));
+                    let rows = catalog_products(&open_catalog(cli.catalog.as_deref()).await, &filter).await;
+                    let products: Vec<models::ProductInfo> = rows.iter().map(|r| r.as_product_info()).collect();
+                    let hits = search::search(&products, &query.join(" "));
+                    if hits.is_empty() {
+                        println!("No products matched '{}'", query.join(" "));
+                    } else {
+                        println!("Products matching '{}':", query.join(" "));
+                        println!("{}", "-".repeat(60));
+                        for hit in hits.iter().take(limit) {
+                            let product = hit.product;
+                            println!("{: <35} || SKU: {: <19} | ID: {} | Size: {}", product.description, product.sku, product.id, format_size(product, cli.units));
+                        }
+                        if hits.len() > limit {
+                            println!("... {} more (use --limit to show more)", hits.len() - limit);
                         }
                     }
                 }
             }
         }
-        Commands::Stores { subcmd } => {
+        Commands::Catalog { subcmd } => {
+            match subcmd {
+                CatalogSubcommand::Refresh { output, force } => {
+                    let path = output.unwrap_or_else(catalog::refreshed_catalog_path);
+                    println!("Scraping the catalog, this takes a while...");
+                    let scrape = scraper::scrape().await.unwrap_or_else(|e| {
+                        eprintln!("Could not scrape the catalog: {}", e);
+                        process::exit(1);
+                    });
+                    let data = scrape.data;
+                    if data.products.is_empty() {
+                        eprintln!("The scrape returned no products; keeping the existing catalog.");
+                        process::exit(1);
+                    }
+                    let current = catalog::CatalogSource::resolve(None)
+                        .read()
+                        .ok()
+                        .and_then(|bytes| serde_json::from_slice::<catalog::ProductsJson>(&bytes).map(|c| c.products.len()).ok());
+                    let mut problems = Vec::new();
+                    if scrape.failures > 0 {
+                        problems.push(format!("{} pages could not be fetched", scrape.failures));
+                    }
+                    if data.stores.is_empty() {
+                        problems.push("no stores were found".to_string());
+                    }
+                    if let Some(current) = current
+                        && (data.products.len() as f64) < current as f64 * (1.0 - MAX_PRODUCT_DROP)
+                    {
+                        problems.push(format!("it found {} products against {} in the current catalog", data.products.len(), current));
+                    }
+                    if !problems.is_empty() {
+                        if !force {
+                            eprintln!("The scrape looks incomplete: {}.", problems.join("; "));
+                            eprintln!("Keeping the existing catalog; use --force to save it anyway.");
+                            process::exit(1);
+                        }
+                        eprintln!("Warning: saving the scrape anyway: {}.", problems.join("; "));
+                    }
+                    if let Err(e) = catalog::write_products_json(&path, &data) {
+                        eprintln!("Could not save the catalog to {}: {}", path.display(), e);
+                        process::exit(1);
+                    }
+                    println!("Saved {} products and {} stores to {}", data.products.len(), data.stores.len(), path.display());
+                    open_catalog(cli.catalog.as_deref()).await;
+                }
+                CatalogSubcommand::Diff { old, new, format } => {
+                    let (old_bytes, new_bytes) = (read_file(&old), read_file(&new));
+                    let old_catalog = parse_products_json(&old, &old_bytes);
+                    let new_catalog = parse_products_json(&new, &new_bytes);
+                    let changes = diff::diff(&old_catalog, &new_catalog);
+                    match format {
+                        DiffFormat::Text => print!("{}", changes),
+                        DiffFormat::Json => println!("{}", serde_json::to_string_pretty(&changes).expect("Failed to serialize diff")),
+                    }
+                }
+                CatalogSubcommand::Info { max_age_days } => {
+                    let source = catalog::CatalogSource::resolve(cli.catalog.as_deref());
+                    let bytes = source.read().unwrap_or_else(|e| {
+                        eprintln!("Could not read the catalog from {}: {}", source, e);
+                        process::exit(1);
+                    });
+                    let data: catalog::ProductsJson = serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                        eprintln!("{} is not a valid products.json: {}", source, e);
+                        process::exit(1);
+                    });
+                    let summary = data.metadata.clone().unwrap_or_else(|| data.compute_metadata(chrono::DateTime::UNIX_EPOCH));
+                    println!("Source:        {}", source);
+                    match &data.metadata {
+                        Some(metadata) => {
+                            println!(
+                                "Scraped:       {} ({} days ago)",
+                                metadata.scraped_at.format("%Y-%m-%d %H:%M UTC"),
+                                metadata.age().num_days()
+                            );
+                            println!("Crate version: {}", metadata.crate_version);
+                            println!("Source URL:    {}", metadata.source_url);
+                        }
+                        None => println!("Scraped:       unknown (this file has no metadata)"),
+                    }
+                    println!("Content hash:  {}", summary.content_hash);
+                    println!("Products:      {}", summary.product_count);
+                    println!("Stores:        {}", summary.store_count);
+                    println!("Metals:");
+                    for (metal, count) in &summary.metals {
+                        println!("    {: <20} {}", metal, count);
+                    }
+                    println!("Shapes:");
+                    for (shape, count) in &summary.shapes {
+                        println!("    {: <20} {}", shape, count);
+                    }
+                    let Some(metadata) = &data.metadata else {
+                        eprintln!("Warning: the catalog's age is unknown. Run `catalog refresh` to update it.");
+                        return;
+                    };
+                    if metadata.age().num_days() > max_age_days {
+                        eprintln!(
+                            "Warning: the catalog is {} days old (limit {}). Run `catalog refresh` to update it.",
+                            metadata.age().num_days(),
+                            max_age_days
+                        );
+                    }
+                    if metadata.content_hash != data.content_hash() {
+                        eprintln!("Warning: the content hash does not match; the file was modified after it was scraped.");
+                    }
+                }
+                CatalogSubcommand::History { sku } => {
+                    let catalog = open_catalog(cli.catalog.as_deref()).await;
+                    if let Some(sku) = sku {
+                        let history = catalog.sku_history(&sku).await.ok().flatten();
+                        write_stdout(|out| print_sku_history(out, &sku, history.as_ref()));
+                        return;
+                    }
+                    let refreshes = catalog.refreshes().await.unwrap_or_default();
+                    let discontinued = catalog.discontinued().await.unwrap_or_default();
+                    write_stdout(|out| print_import_history(out, &refreshes, &discontinued));
+                }
+            }
+        }
+        Commands::Prices { subcmd } => {
+            match subcmd {
+                PricesSubcommand::Model { sku, store, lengths, quantities, width, no_cache } => {
+                    let catalog = open_catalog(cli.catalog.as_deref()).await;
+                    let Some(product) = catalog.products_by_sku(&sku).await.unwrap_or_default().into_iter().next() else {
+                        eprintln!("SKU '{}' is not in the catalog.", sku);
+                        process::exit(1);
+                    };
+                    let parse_length = |s: &str| {
+                        units::Length::parse(s, cli.units).unwrap_or_else(|e| {
+                            eprintln!("{}.", e);
+                            process::exit(1);
+                        })
+                    };
+                    let lengths: Vec<units::Length> = lengths.iter().map(|l| parse_length(l)).collect();
+                    let width = product.requires_width.then(|| parse_length(&width));
+                    if quantities.contains(&0) {
+                        eprintln!("Quantities must be at least 1.");
+                        process::exit(1);
+                    }
+                    let basis = if width.is_some() { cost_model::SizeBasis::Area } else { cost_model::SizeBasis::Length };
+                    let parts: Vec<Part> = quantities
+                        .iter()
+                        .flat_map(|&quantity| lengths.iter().map(move |&length| (quantity, length)))
+                        .map(|(quantity, length)| Part {
+                            id: product.id.clone(),
+                            sku: product.sku.clone(),
+                            qualifier_a: product.qualifier_a.clone(),
+                            qualifier_b: product.qualifier_b.clone(),
+                            qualifier_c: product.qualifier_c.clone(),
+                            length: Some(length),
+                            width,
+                            units: cli.units,
+                            quantity: quantity as usize,
+                        })
+                        .collect();
+                    println!("Sampling {} prices for {} at store {}...", parts.len(), sku, store);
+                    let pricer = LinePricer::open(no_cache, pricing::DEFAULT_CACHE_TTL, None).await;
+                    let lines = pricer.price_all(&store, &parts).await;
+                    let mut samples = Vec::new();
+                    let mut currency = None;
+                    for (part, line) in parts.iter().zip(&lines) {
+                        match &line.quote {
+                            Ok(quote) => {
+                                let length = part.length.map_or(0.0, |l| l.inches());
+                                let size = part.width.map_or(length, |w| length * w.inches());
+                                samples.push(cost_model::PriceSample { size, quantity: part.quantity as u32, unit_price: quote.unit_price });
+                                currency.get_or_insert_with(|| quote.currency.clone());
+                            }
+                            Err(e) => eprintln!(
+                                "Could not price {} x{} at {}: {}",
+                                sku,
+                                part.quantity,
+                                part.length.map(|l| l.to_string()).unwrap_or_default(),
+                                e
+                            ),
+                        }
+                    }
+                    let currency = currency.unwrap_or_else(|| "USD".to_string());
+                    let model = cost_model::CostModel::fit(&sku, &store, basis, &currency, &samples).unwrap_or_else(|e| {
+                        eprintln!("Could not fit a cost model: {}.", e);
+                        process::exit(1);
+                    });
+                    if let Err(e) = pricer.history.save_model(&model).await {
+                        eprintln!("Could not save the cost model: {}", e);
+                        process::exit(1);
+                    }
+                    println!("Cost model for {} at store {} ({} of {} prices sampled):", sku, store, samples.len(), parts.len());
+                    println!("Cut charge: {} per piece", pricing::format_money(model.cut_charge, &currency));
+                    let sampled: std::collections::BTreeSet<u32> = samples.iter().map(|s| s.quantity).collect();
+                    for (i, rate_break) in model.breaks.iter().enumerate() {
+                        let rate = match basis {
+                            cost_model::SizeBasis::Length => pricing::PriceRate::PerFoot(rate_break.rate * 12.0),
+                            cost_model::SizeBasis::Area => pricing::PriceRate::PerSquareFoot(rate_break.rate * 144.0),
+                        };
+                        // The break is somewhere past the last quantity sampled at the old rate.
+                        let between = sampled
+                            .range(..rate_break.min_quantity)
+                            .next_back()
+                            .filter(|&&below| i > 0 && below + 1 < rate_break.min_quantity)
+                            .map(|below| format!(" (the break is between {} and {})", below + 1, rate_break.min_quantity))
+                            .unwrap_or_default();
+                        println!(
+                            "{} {} from {} piece(s){}",
+                            if i == 0 { "Rate:      " } else { "           " },
+                            format_price_rate(rate, cli.units, &currency),
+                            rate_break.min_quantity,
+                            between
+                        );
+                    }
+                    println!("Fit:        worst error {:.1}%", model.max_error * 100.0);
+                    if model.max_error > 0.05 {
+                        println!("Some prices are more than 5% off the model; this product may not price as a cut charge plus a rate.");
+                    }
+                    if model.breaks.len() > 1 {
+                        println!("Breaks are placed at the sampled quantities; add --quantities inside a range above to pin one down.");
+                    }
+                }
+                PricesSubcommand::History { sku, store, format } => {
+                    let path = pricing::default_history_path();
+                    let history = pricing::PriceHistory::open(&path).await.unwrap_or_else(|e| {
+                        eprintln!("Could not open the price history at {}: {}", path.display(), e);
+                        process::exit(1);
+                    });
+                    let records = history.for_sku(&sku, store.as_deref()).await.unwrap_or_else(|e| {
+                        eprintln!("Could not read the price history at {}: {}", path.display(), e);
+                        process::exit(1);
+                    });
+                    write_stdout(|out| print_price_history(out, &sku, &records, format, cli.units));
+                }
+            }
+        }
+        Commands::Design { subcmd } => {
             match subcmd {
-                Subcommand::List => {
-                    let stores = bundled_products().stores;
-                    if stores.is_empty() {
-                        println!("No products found in bundled products");
+                DesignSubcommand::Beam {
+                    span,
+                    load,
+                    load_type,
+                    support,
+                    deflection_limit,
+                    safety_factor,
+                    no_self_weight,
+                    metal,
+                    shape,
+                    grade,
+                    sort,
+                    store,
+                    limit,
+                } => {
+                    if shape == Some(models::Shape::Angle) {
+                        eprintln!("Angles can't be checked as beams: their section properties are about the legs, not the principal axes they bend about.");
+                        process::exit(1);
+                    }
+                    let span = units::Length::parse(&span, cli.units).unwrap_or_else(|e| {
+                        eprintln!("Invalid --span: {}.", e);
+                        process::exit(1);
+                    });
+                    let load = design::parse_force(&load).unwrap_or_else(|e| {
+                        eprintln!("Invalid --load: {}.", e);
+                        process::exit(1);
+                    });
+                    for (flag, value) in [("--deflection-limit", deflection_limit), ("--safety-factor", safety_factor)] {
+                        if !value.is_finite() || value <= 0.0 {
+                            eprintln!("Invalid {}: {} (expected a number greater than zero).", flag, value);
+                            process::exit(1);
+                        }
+                    }
+                    let case = design::BeamCase {
+                        span: span.inches(),
+                        load,
+                        load_kind: load_type,
+                        support,
+                        deflection_limit,
+                        safety_factor,
+                        self_weight: !no_self_weight,
+                    };
+                    let filter = search::ProductFilter { metal, shape, grade, ..Default::default() };
+                    let rows = catalog_products(&open_catalog(cli.catalog.as_deref()).await, &filter).await;
+                    let products: Vec<models::ProductInfo> = rows.iter().map(|r| r.as_product_info()).collect();
+                    let candidates = design::select_beams(&products, &filter, &case);
+                    if candidates.is_empty() {
+                        println!("No stocked product carries that load within the limits");
+                        return;
+                    }
+                    let mut prices: Vec<Option<PricedLine>> = Vec::with_capacity(candidates.len());
+                    if let Some(store) = &store {
+                        let pricer = LinePricer::open(false, pricing::DEFAULT_CACHE_TTL, None).await;
+                        for candidate in &candidates {
+                            let product = candidate.product;
+                            let part = Part {
+                                id: product.id.to_string(),
+                                sku: product.sku.to_string(),
+                                qualifier_a: product.qualifier_a.to_string(),
+                                qualifier_b: product.qualifier_b.to_string(),
+                                qualifier_c: product.qualifier_c.to_string(),
+                                length: Some(span),
+                                width: None,
+                                units: cli.units,
+                                quantity: 1,
+                            };
+                            prices.push(Some(pricer.lookup(store, &part, cost_model::DEFAULT_MAX_AGE).await));
+                        }
                     } else {
-                        println!("Stores:");
-                        println!("{}", "-".repeat(60));
-                        for store in &stores {
-                            println!("{} || ID: {}", format!("{: <30}", store.name), store.id);
+                        prices.resize_with(candidates.len(), || None);
+                    }
+                    let price_of = |i: usize| prices[i].as_ref().and_then(|line| line.quote.as_ref().ok()).map(|q| q.extended_price);
+                    let mut order: Vec<usize> = (0..candidates.len()).collect();
+                    if sort == BeamSort::Price {
+                        // Stable, so unpriced products stay lightest first after the priced ones.
+                        order.sort_by(|&a, &b| match (price_of(a), price_of(b)) {
+                            (Some(a), Some(b)) => a.total_cmp(&b),
+                            (Some(_), None) => std::cmp::Ordering::Less,
+                            (None, Some(_)) => std::cmp::Ordering::Greater,
+                            (None, None) => std::cmp::Ordering::Equal,
+                        });
+                    }
+                    match sort {
+                        BeamSort::Weight => println!("Products that carry {:.0} lb over {}, lightest first:", load, span),
+                        BeamSort::Price => println!("Products that carry {:.0} lb over {}, cheapest first:", load, span),
+                    }
+                    println!("{}", "-".repeat(60));
+                    for &i in order.iter().take(limit) {
+                        let product = candidates[i].product;
+                        let check = &candidates[i].check;
+                        let mut line = format!(
+                            "{: <35} || SKU: {: <19} | Size: {} | {} | Stress: {:.0}% | Deflection: {} ({:.0}%)",
+                            product.description,
+                            product.sku,
+                            format_size(product, cli.units),
+                            format_weight_rate(weight::WeightRate::PerFoot(check.weight_per_foot), cli.units),
+                            check.stress / check.allowable_stress * 100.0,
+                            format_significant(cli.units.from_inches(check.deflection)) + " " + cli.units.symbol(),
+                            check.deflection / check.allowable_deflection * 100.0
+                        );
+                        if let Some(priced) = &prices[i] {
+                            match &priced.quote {
+                                Ok(quote) => {
+                                    line.push_str(&format!(" | Price: {}", pricing::format_money(quote.extended_price, &quote.currency)));
+                                    if !priced.cached {
+                                        line.push_str(" (est.)");
+                                    }
+                                    if priced.stale {
+                                        line.push('!');
+                                    }
+                                    if priced.extrapolated {
+                                        line.push('~');
+                                    }
+                                }
+                                Err(_) => line.push_str(" | Price: -"),
+                            }
+                        }
+                        if check.needs_bracing {
+                            line.push_str(" | Needs bracing");
+                        }
+                        println!("{}", line);
+                    }
+                    if candidates.len() > limit {
+                        println!("... {} more (use --limit to show more)", candidates.len() - limit);
+                    }
+                    let shown = || order.iter().take(limit);
+                    if shown().any(|&i| candidates[i].check.needs_bracing) {
+                        println!("Needs bracing: lateral-torsional buckling isn't checked for channel, beam and tee; brace the compression flange.");
+                    }
+                    if let Some(store) = &store {
+                        if (0..candidates.len()).all(|i| price_of(i).is_none()) {
+                            println!("No cached prices or cost models at store {}; run `quote` or `prices model` to get some.", store);
+                        } else {
+                            if shown().any(|&i| prices[i].as_ref().is_some_and(|line| line.stale)) {
+                                println!("!: estimated from a cost model older than {} days.", cost_model::DEFAULT_MAX_AGE.num_days());
+                            }
+                            if shown().any(|&i| prices[i].as_ref().is_some_and(|line| line.extrapolated)) {
+                                println!("~: outside the lengths the cost model was fitted to.");
+                            }
                         }
                     }
                 }
             }
         }
+        Commands::Stores { subcmd } => {
+            match subcmd {
+                Subcommand::List { format } => {
+                    let stores = open_catalog(cli.catalog.as_deref()).await.stores().await.unwrap_or_else(|e| {
+                        eprintln!("Could not read stores from the catalog: {}", e);
+                        process::exit(1);
+                    });
+                    write_stdout(|out| print_stores(out, &stores, format));
+                }
+            }
+        }
     }
 }
@@ -1,3 +1,4 @@
+use super::pricing::{PriceError, PriceQuote};
 use reqwest::{Client, Response, header};
 use reqwest::cookie::Jar;
 use std::sync::Arc;
@@ -24,6 +25,7 @@ pub struct GetProductPriceParams<'a> {
     pub store_country: &'a str,
     pub pro_id: &'a str,
     pub pro_sku: &'a str,
+    pub prowidth: Option<&'a str>,
     pub prolength: &'a str,
     pub selunits: &'a str,
     pub selquantity: &'a str,
@@ -33,6 +35,12 @@ pub struct ApiClient {
     pub cookie_jar: Arc<Jar>,
 }
 
+impl Default for ApiClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ApiClient {
     pub fn new() -> Self {
         let cookie_jar = Arc::new(Jar::default());
@@ -87,16 +95,22 @@ impl ApiClient {
             .await
     }
 
-    pub async fn get_product_price<'a>(&self, params: GetProductPriceParams<'a>) -> Result<Response, reqwest::Error> {
-        let body = serde_urlencoded::to_string(&params).unwrap();
-        self.client
+    /// Prices a line without adding it to the cart. Go through
+    /// [`PriceHistory::fetch`](super::pricing::PriceHistory::fetch) to keep
+    /// the price in the price history.
+    pub async fn get_product_price(&self, params: &GetProductPriceParams<'_>) -> Result<PriceQuote, PriceError> {
+        let quantity = params.selquantity.trim().parse().unwrap_or(1);
+        let body = serde_urlencoded::to_string(params).unwrap();
+        let resp = self.client
             .post("https://www.metalsupermarkets.com/wp-admin/admin-ajax.php")
             .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
             .header("x-requested-with", "XMLHttpRequest")
             .body(body)
             .send()
-            .await
+            .await?;
+        if !resp.status().is_success() {
+            return Err(PriceError::Status(resp.status()));
+        }
+        PriceQuote::parse(&resp.text().await?, quantity)
     }
 }
-
-
@@ -0,0 +1,661 @@
+use super::models::{ProductInfo, Store};
+use super::search::ProductFilter;
+use super::substitutes::{substitutes, SubstituteKind};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::Row;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Where the scraper starts crawling the catalog.
+pub const SOURCE_URL: &str = "https://www.metalsupermarkets.com/metals";
+
+/// The `{ "metadata": {...}, "products": [...], "stores": [...] }` document
+/// written by [`gather`](super::scraper::gather) and bundled into the binary.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "'de: 'a"))]
+pub struct ProductsJson<'a> {
+    /// Provenance of the scrape. Missing from files written before it was added.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<CatalogMetadata>,
+    pub products: Vec<ProductInfo<'a>>,
+    pub stores: Vec<Store>,
+}
+
+/// Provenance and summary counts for a scraped catalog.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogMetadata {
+    pub scraped_at: DateTime<Utc>,
+    /// Version of this crate that produced the file.
+    pub crate_version: String,
+    pub source_url: String,
+    pub product_count: usize,
+    pub store_count: usize,
+    /// Products per metal family; products whose description doesn't parse count as "other".
+    pub metals: BTreeMap<String, usize>,
+    /// Products per shape, counted like `metals`.
+    pub shapes: BTreeMap<String, usize>,
+    /// See [`ProductsJson::content_hash`].
+    pub content_hash: String,
+}
+
+impl CatalogMetadata {
+    pub fn age(&self) -> chrono::Duration {
+        Utc::now() - self.scraped_at
+    }
+}
+
+impl<'a> ProductsJson<'a> {
+    /// A document without metadata; see [`ProductsJson::with_metadata`].
+    pub fn new(products: Vec<ProductInfo<'a>>, stores: Vec<Store>) -> ProductsJson<'a> {
+        ProductsJson { metadata: None, products, stores }
+    }
+
+    /// Attaches metadata describing the current products and stores, scraped at `scraped_at`.
+    pub fn with_metadata(mut self, scraped_at: DateTime<Utc>) -> ProductsJson<'a> {
+        self.metadata = Some(self.compute_metadata(scraped_at));
+        self
+    }
+
+    pub fn compute_metadata(&self, scraped_at: DateTime<Utc>) -> CatalogMetadata {
+        let mut metals = BTreeMap::new();
+        let mut shapes = BTreeMap::new();
+        for product in &self.products {
+            let material = product.material();
+            let metal = material.as_ref().map_or("other", |m| m.metal.name());
+            let shape = material.as_ref().map_or("other", |m| m.shape.name());
+            *metals.entry(metal.to_string()).or_insert(0) += 1;
+            *shapes.entry(shape.to_string()).or_insert(0) += 1;
+        }
+        CatalogMetadata {
+            scraped_at,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            source_url: SOURCE_URL.to_string(),
+            product_count: self.products.len(),
+            store_count: self.stores.len(),
+            metals,
+            shapes,
+            content_hash: self.content_hash(),
+        }
+    }
+
+    /// SHA-256 of the products and stores, independent of the file's
+    /// formatting and metadata.
+    pub fn content_hash(&self) -> String {
+        let content = serde_json::to_vec(&(&self.products, &self.stores)).expect("Failed to serialize catalog");
+        Sha256::digest(&content).iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+static BUNDLED: &[u8] = include_bytes!("../resources/products.json");
+
+/// Returns the product list bundled at build time.
+pub fn bundled_products() -> ProductsJson<'static> {
+    serde_json::from_slice(BUNDLED).expect("Failed to parse bundled products.json")
+}
+
+/// Writes a products.json document, replacing `path` only once the new
+/// contents are fully on disk.
+pub fn write_products_json(path: &Path, data: &ProductsJson) -> std::io::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    let file = std::fs::File::create(&tmp)?;
+    serde_json::to_writer_pretty(file, data)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Fingerprint of a catalog source, used to decide when the database needs
+/// to be re-imported.
+pub fn source_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Per-user data directory: `$MSRS_DATA_DIR`, else `$XDG_DATA_HOME/msrs`,
+/// else `~/.local/share/msrs`.
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("MSRS_DATA_DIR") {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME") {
+        return PathBuf::from(dir).join("msrs");
+    }
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".local").join("share").join("msrs"),
+        None => PathBuf::from(".msrs"),
+    }
+}
+
+/// Per-user cache directory: `$MSRS_CACHE_DIR`, else `$XDG_CACHE_HOME/msrs`,
+/// else `~/.cache/msrs`.
+pub fn cache_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("MSRS_CACHE_DIR") {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = std::env::var_os("XDG_CACHE_HOME") {
+        return PathBuf::from(dir).join("msrs");
+    }
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".cache").join("msrs"),
+        None => PathBuf::from(".msrs").join("cache"),
+    }
+}
+
+/// Where `catalog refresh` saves a freshly scraped products.json.
+pub fn refreshed_catalog_path() -> PathBuf {
+    cache_dir().join("products.json")
+}
+
+/// Where the active catalog comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogSource {
+    /// A products.json given explicitly, e.g. with `--catalog`.
+    File(PathBuf),
+    /// The products.json saved by the last `catalog refresh`.
+    Refreshed(PathBuf),
+    /// The products.json compiled into the binary.
+    Bundled,
+}
+
+impl CatalogSource {
+    /// Picks `explicit` if given, else a refreshed catalog in the cache
+    /// directory if one exists, else the bundled catalog.
+    pub fn resolve(explicit: Option<&Path>) -> CatalogSource {
+        if let Some(path) = explicit {
+            return CatalogSource::File(path.to_path_buf());
+        }
+        let refreshed = refreshed_catalog_path();
+        if refreshed.is_file() {
+            CatalogSource::Refreshed(refreshed)
+        } else {
+            CatalogSource::Bundled
+        }
+    }
+
+    pub fn read(&self) -> std::io::Result<Vec<u8>> {
+        match self {
+            CatalogSource::File(path) | CatalogSource::Refreshed(path) => std::fs::read(path),
+            CatalogSource::Bundled => Ok(BUNDLED.to_vec()),
+        }
+    }
+}
+
+impl std::fmt::Display for CatalogSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CatalogSource::File(path) => write!(f, "{}", path.display()),
+            CatalogSource::Refreshed(path) => write!(f, "{} (refreshed)", path.display()),
+            CatalogSource::Bundled => f.write_str("bundled catalog"),
+        }
+    }
+}
+
+/// Default location of the catalog database.
+pub fn default_database_path() -> PathBuf {
+    data_dir().join("catalog.db")
+}
+
+/// A product row as stored in the catalog database. Owns its strings; use
+/// [`ProductRow::as_product_info`] to borrow it as a [`ProductInfo`].
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct ProductRow {
+    pub sku: String,
+    pub id: String,
+    pub qualifier_a: String,
+    pub qualifier_b: String,
+    pub qualifier_c: String,
+    pub description: String,
+    pub requires_length: bool,
+    pub requires_width: bool,
+}
+
+impl ProductRow {
+    pub fn as_product_info(&self) -> ProductInfo<'_> {
+        ProductInfo {
+            sku: &self.sku,
+            id: &self.id,
+            qualifier_a: &self.qualifier_a,
+            qualifier_b: &self.qualifier_b,
+            qualifier_c: &self.qualifier_c,
+            description: &self.description,
+            requires_length: self.requires_length,
+            requires_width: self.requires_width,
+        }
+    }
+}
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS products (
+        row_id INTEGER PRIMARY KEY,
+        sku TEXT NOT NULL,
+        id TEXT NOT NULL,
+        qualifier_a TEXT NOT NULL,
+        qualifier_b TEXT NOT NULL,
+        qualifier_c TEXT NOT NULL,
+        description TEXT NOT NULL,
+        requires_length INTEGER NOT NULL,
+        requires_width INTEGER NOT NULL,
+        metal TEXT,
+        shape TEXT,
+        grade TEXT
+    )",
+    "CREATE INDEX IF NOT EXISTS products_sku ON products (sku)",
+    "CREATE INDEX IF NOT EXISTS products_id ON products (id)",
+    "CREATE INDEX IF NOT EXISTS products_description ON products (description COLLATE NOCASE)",
+    "CREATE INDEX IF NOT EXISTS products_material ON products (metal, shape, grade COLLATE NOCASE)",
+    "CREATE TABLE IF NOT EXISTS stores (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS refreshes (
+        refresh_id INTEGER PRIMARY KEY,
+        recorded_at INTEGER NOT NULL,
+        source_hash TEXT NOT NULL,
+        product_count INTEGER NOT NULL,
+        store_count INTEGER NOT NULL
+    )",
+    // The most recent entry for every SKU ever imported, so discontinued
+    // products can still be described.
+    "CREATE TABLE IF NOT EXISTS known_products (
+        sku TEXT PRIMARY KEY,
+        id TEXT NOT NULL,
+        qualifier_a TEXT NOT NULL,
+        qualifier_b TEXT NOT NULL,
+        qualifier_c TEXT NOT NULL,
+        description TEXT NOT NULL,
+        requires_length INTEGER NOT NULL,
+        requires_width INTEGER NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS sku_history (
+        sku TEXT PRIMARY KEY,
+        first_seen INTEGER NOT NULL,
+        last_seen INTEGER NOT NULL,
+        discontinued_at INTEGER
+    )",
+];
+
+/// When a SKU was first and last present in an imported catalog.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkuHistory {
+    pub sku: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Set once an import no longer lists the SKU; cleared if it comes back.
+    pub discontinued_at: Option<DateTime<Utc>>,
+}
+
+impl SkuHistory {
+    pub fn is_discontinued(&self) -> bool {
+        self.discontinued_at.is_some()
+    }
+}
+
+/// One import of a catalog source into the database.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Refresh {
+    pub recorded_at: DateTime<Utc>,
+    pub source_hash: String,
+    pub product_count: i64,
+    pub store_count: i64,
+}
+
+fn timestamp(secs: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs, 0).unwrap_or_default()
+}
+
+fn sku_history_from_row(row: &sqlx::sqlite::SqliteRow) -> SkuHistory {
+    SkuHistory {
+        sku: row.get("sku"),
+        first_seen: timestamp(row.get("first_seen")),
+        last_seen: timestamp(row.get("last_seen")),
+        discontinued_at: row.get::<Option<i64>, _>("discontinued_at").map(timestamp),
+    }
+}
+
+const PRODUCT_COLUMNS: &str = "sku, id, qualifier_a, qualifier_b, qualifier_c, description, requires_length, requires_width";
+
+/// Local SQLite copy of the product and store catalog.
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    pool: SqlitePool,
+}
+
+impl Catalog {
+    /// Opens (creating if needed) the catalog database at `path`.
+    pub async fn open(path: &Path) -> Result<Catalog, sqlx::Error> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let options = SqliteConnectOptions::new().filename(path).create_if_missing(true);
+        let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await?;
+        let catalog = Catalog { pool };
+        catalog.migrate().await?;
+        Ok(catalog)
+    }
+
+    /// Opens an empty catalog that lives only as long as the returned value.
+    pub async fn in_memory() -> Result<Catalog, sqlx::Error> {
+        let options = SqliteConnectOptions::new().in_memory(true);
+        let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await?;
+        let catalog = Catalog { pool };
+        catalog.migrate().await?;
+        Ok(catalog)
+    }
+
+    async fn migrate(&self) -> Result<(), sqlx::Error> {
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Replaces all products and stores with `data`, recording `source_hash`
+    /// so [`Catalog::sync`] can skip unchanged sources.
+    pub async fn import(&self, data: &ProductsJson<'_>, source_hash: &str) -> Result<(), sqlx::Error> {
+        self.import_at(data, source_hash, Utc::now()).await
+    }
+
+    /// Like [`Catalog::import`], but records the import as happening at
+    /// `recorded_at`. Every SKU in `data` is marked seen at that time, and
+    /// SKUs missing from `data` are marked discontinued.
+    pub async fn import_at(&self, data: &ProductsJson<'_>, source_hash: &str, recorded_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        let now = recorded_at.timestamp();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM products").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM stores").execute(&mut *tx).await?;
+        for product in &data.products {
+            let material = product.material();
+            sqlx::query(&format!("INSERT INTO products ({}, metal, shape, grade) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)", PRODUCT_COLUMNS))
+                .bind(product.sku)
+                .bind(product.id)
+                .bind(product.qualifier_a)
+                .bind(product.qualifier_b)
+                .bind(product.qualifier_c)
+                .bind(product.description)
+                .bind(product.requires_length)
+                .bind(product.requires_width)
+                .bind(material.as_ref().map(|m| m.metal.name()))
+                .bind(material.as_ref().map(|m| m.shape.name()))
+                .bind(material.and_then(|m| m.grade))
+                .execute(&mut *tx)
+                .await?;
+        }
+        for store in &data.stores {
+            sqlx::query("INSERT OR REPLACE INTO stores (id, name) VALUES (?, ?)")
+                .bind(&store.id)
+                .bind(&store.name)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("INSERT OR REPLACE INTO meta (key, value) VALUES ('source_hash', ?)")
+            .bind(source_hash)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO refreshes (recorded_at, source_hash, product_count, store_count) VALUES (?, ?, ?, ?)")
+            .bind(now)
+            .bind(source_hash)
+            .bind(data.products.len() as i64)
+            .bind(data.stores.len() as i64)
+            .execute(&mut *tx)
+            .await?;
+        // `WHERE true` keeps SQLite from reading ON CONFLICT as a join clause.
+        sqlx::query(
+            "INSERT INTO sku_history (sku, first_seen, last_seen, discontinued_at)
+             SELECT DISTINCT sku, ?1, ?1, NULL FROM products WHERE true
+             ON CONFLICT (sku) DO UPDATE SET
+                 first_seen = MIN(first_seen, excluded.first_seen),
+                 last_seen = MAX(last_seen, excluded.last_seen),
+                 discontinued_at = NULL",
+        )
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(&format!("INSERT OR REPLACE INTO known_products ({0}) SELECT {0} FROM products ORDER BY row_id", PRODUCT_COLUMNS))
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE sku_history SET discontinued_at = ? WHERE discontinued_at IS NULL AND sku NOT IN (SELECT sku FROM products)")
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await
+    }
+
+    /// Imports `bytes` (a products.json document) unless it is the source
+    /// the database was last imported from. Returns whether it imported.
+    pub async fn sync(&self, bytes: &[u8]) -> Result<bool, sqlx::Error> {
+        let hash = source_hash(bytes);
+        if self.meta("source_hash").await?.as_deref() == Some(hash.as_str()) {
+            return Ok(false);
+        }
+        let data: ProductsJson = serde_json::from_slice(bytes).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        // Date history by when the catalog was scraped, when known.
+        let recorded_at = data.metadata.as_ref().map_or_else(Utc::now, |m| m.scraped_at);
+        self.import_at(&data, &hash, recorded_at).await?;
+        Ok(true)
+    }
+
+    /// Imports the bundled products.json if the database is not already
+    /// up to date with it.
+    pub async fn sync_bundled(&self) -> Result<bool, sqlx::Error> {
+        self.sync(BUNDLED).await
+    }
+
+    pub async fn meta(&self, key: &str) -> Result<Option<String>, sqlx::Error> {
+        let row = sqlx::query("SELECT value FROM meta WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| r.get("value")))
+    }
+
+    /// All products, in the order they were imported.
+    pub async fn products(&self) -> Result<Vec<ProductRow>, sqlx::Error> {
+        sqlx::query_as(&format!("SELECT {} FROM products ORDER BY row_id", PRODUCT_COLUMNS))
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Products of the metal, shape and grade `filter` asks for, in the
+    /// order they were imported. Size bounds are not applied here; pass the
+    /// result through [`ProductFilter::apply`] for those.
+    pub async fn products_matching(&self, filter: &ProductFilter) -> Result<Vec<ProductRow>, sqlx::Error> {
+        let mut conditions = Vec::new();
+        if filter.metal.is_some() {
+            conditions.push("metal = ?");
+        }
+        if filter.shape.is_some() {
+            conditions.push("shape = ?");
+        }
+        if filter.grade.is_some() {
+            conditions.push("grade = ? COLLATE NOCASE");
+        }
+        let mut sql = format!("SELECT {} FROM products", PRODUCT_COLUMNS);
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY row_id");
+        let mut query = sqlx::query_as::<_, ProductRow>(&sql);
+        if let Some(metal) = filter.metal {
+            query = query.bind(metal.name());
+        }
+        if let Some(shape) = filter.shape {
+            query = query.bind(shape.name());
+        }
+        if let Some(grade) = &filter.grade {
+            query = query.bind(grade.as_str());
+        }
+        query.fetch_all(&self.pool).await
+    }
+
+    pub async fn products_by_sku(&self, sku: &str) -> Result<Vec<ProductRow>, sqlx::Error> {
+        sqlx::query_as(&format!("SELECT {} FROM products WHERE sku = ? ORDER BY row_id", PRODUCT_COLUMNS))
+            .bind(sku)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    pub async fn products_by_id(&self, id: &str) -> Result<Vec<ProductRow>, sqlx::Error> {
+        sqlx::query_as(&format!("SELECT {} FROM products WHERE id = ? ORDER BY row_id", PRODUCT_COLUMNS))
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Products whose description matches exactly, ignoring case.
+    pub async fn products_by_description(&self, description: &str) -> Result<Vec<ProductRow>, sqlx::Error> {
+        sqlx::query_as(&format!("SELECT {} FROM products WHERE description = ? COLLATE NOCASE ORDER BY row_id", PRODUCT_COLUMNS))
+            .bind(description)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    pub async fn stores(&self) -> Result<Vec<Store>, sqlx::Error> {
+        sqlx::query_as("SELECT id, name FROM stores ORDER BY rowid")
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// First- and last-seen dates for `sku`, if any import ever listed it.
+    pub async fn sku_history(&self, sku: &str) -> Result<Option<SkuHistory>, sqlx::Error> {
+        let row = sqlx::query("SELECT sku, first_seen, last_seen, discontinued_at FROM sku_history WHERE sku = ?")
+            .bind(sku)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(sku_history_from_row))
+    }
+
+    /// SKUs that have dropped out of the catalog, most recently discontinued first.
+    pub async fn discontinued(&self) -> Result<Vec<SkuHistory>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT sku, first_seen, last_seen, discontinued_at FROM sku_history
+             WHERE discontinued_at IS NOT NULL ORDER BY discontinued_at DESC, sku",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(sku_history_from_row).collect())
+    }
+
+    /// Every recorded import, oldest first.
+    pub async fn refreshes(&self) -> Result<Vec<Refresh>, sqlx::Error> {
+        let rows = sqlx::query("SELECT recorded_at, source_hash, product_count, store_count FROM refreshes ORDER BY refresh_id")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| Refresh {
+                recorded_at: timestamp(row.get("recorded_at")),
+                source_hash: row.get("source_hash"),
+                product_count: row.get("product_count"),
+                store_count: row.get("store_count"),
+            })
+            .collect())
+    }
+
+    /// The catalog entry for `sku` as of the last import that listed it,
+    /// whether or not it is still listed.
+    pub async fn last_known_product(&self, sku: &str) -> Result<Option<ProductRow>, sqlx::Error> {
+        sqlx::query_as(&format!("SELECT {} FROM known_products WHERE sku = ?", PRODUCT_COLUMNS))
+            .bind(sku)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Listed products that could replace `wanted`; see [`substitutes`].
+    pub async fn substitutes(&self, wanted: &ProductInfo<'_>) -> Result<Vec<(ProductRow, SubstituteKind)>, sqlx::Error> {
+        let rows = self.products().await?;
+        let products: Vec<ProductInfo> = rows.iter().map(|r| r.as_product_info()).collect();
+        Ok(substitutes(&products, wanted)
+            .into_iter()
+            .map(|s| (rows[product_index(&products, s.product)].clone(), s.kind))
+            .collect())
+    }
+}
+
+fn product_index(products: &[ProductInfo], product: &ProductInfo) -> usize {
+    products.iter().position(|p| std::ptr::eq(p, product)).expect("substitute is one of the given products")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(sku: &str) -> ProductInfo<'_> {
+        ProductInfo {
+            sku,
+            id: "12271",
+            qualifier_a: "0.25",
+            qualifier_b: "1",
+            qualifier_c: "",
+            description: "ALUMINUM 6061 FLAT BAR",
+            requires_length: true,
+            requires_width: false,
+        }
+    }
+
+    fn day(n: i64) -> DateTime<Utc> {
+        timestamp(1_700_000_000 + n * 86_400)
+    }
+
+    async fn import(catalog: &Catalog, skus: &[&str], on: i64) {
+        let data = ProductsJson::new(skus.iter().map(|sku| product(sku)).collect(), vec![]);
+        catalog.import_at(&data, &format!("import {}", on), day(on)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn marks_skus_dropped_by_a_later_import_discontinued() {
+        let catalog = Catalog::in_memory().await.unwrap();
+        import(&catalog, &["A", "B", "C"], 0).await;
+        import(&catalog, &["A", "C"], 1).await;
+        import(&catalog, &["A"], 2).await;
+
+        let discontinued = catalog.discontinued().await.unwrap();
+        assert_eq!(discontinued.iter().map(|h| h.sku.as_str()).collect::<Vec<_>>(), ["C", "B"]);
+        let b = catalog.sku_history("B").await.unwrap().unwrap();
+        assert_eq!((b.first_seen, b.last_seen, b.discontinued_at), (day(0), day(0), Some(day(1))));
+        let a = catalog.sku_history("A").await.unwrap().unwrap();
+        assert_eq!((a.first_seen, a.last_seen, a.discontinued_at), (day(0), day(2), None));
+        assert_eq!(catalog.sku_history("Z").await.unwrap(), None);
+        assert_eq!(catalog.refreshes().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn a_returning_sku_is_listed_again_and_keeps_its_first_seen() {
+        let catalog = Catalog::in_memory().await.unwrap();
+        import(&catalog, &["A", "B"], 0).await;
+        import(&catalog, &["A"], 1).await;
+        import(&catalog, &["A", "B"], 2).await;
+
+        assert!(catalog.discontinued().await.unwrap().is_empty());
+        let b = catalog.sku_history("B").await.unwrap().unwrap();
+        assert_eq!((b.first_seen, b.last_seen), (day(0), day(2)));
+        assert!(!b.is_discontinued());
+    }
+
+    #[tokio::test]
+    async fn a_discontinued_sku_keeps_the_date_it_first_went_missing() {
+        let catalog = Catalog::in_memory().await.unwrap();
+        import(&catalog, &["A", "B"], 0).await;
+        import(&catalog, &["A"], 1).await;
+        import(&catalog, &["A"], 2).await;
+
+        let b = catalog.sku_history("B").await.unwrap().unwrap();
+        assert_eq!(b.discontinued_at, Some(day(1)));
+        assert_eq!(catalog.products().await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,287 @@
+use super::pricing::PriceQuote;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Relative change in the fitted rate between two sampled quantities that
+/// counts as a quantity break rather than noise.
+const BREAK_TOLERANCE: f64 = 0.02;
+
+/// Age past which estimates from a model are flagged as stale.
+pub const DEFAULT_MAX_AGE: chrono::Duration = chrono::Duration::days(30);
+
+/// What a product's price scales with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeBasis {
+    /// Cut length in inches.
+    Length,
+    /// Cut length times width in square inches, for sheet and plate.
+    Area,
+}
+
+/// One probed price: a piece `size` (inches or square inches) bought
+/// `quantity` at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriceSample {
+    pub size: f64,
+    pub quantity: u32,
+    pub unit_price: f64,
+}
+
+/// The rate that applies from `min_quantity` pieces up.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateBreak {
+    pub min_quantity: u32,
+    /// Price per inch, or per square inch for [`SizeBasis::Area`].
+    pub rate: f64,
+}
+
+/// A product's price at a store as a fixed charge per piece plus a rate per
+/// unit of size, with the rate stepping down at quantity breaks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostModel {
+    pub sku: String,
+    pub store_id: String,
+    pub basis: SizeBasis,
+    /// Fixed charge per piece, whatever its size; mostly the cut.
+    pub cut_charge: f64,
+    /// Ascending by `min_quantity`; the first starts at the smallest
+    /// quantity sampled.
+    pub breaks: Vec<RateBreak>,
+    pub currency: String,
+    pub fitted_at: DateTime<Utc>,
+    pub samples: usize,
+    /// Worst relative difference between a sample and the model's estimate.
+    pub max_error: f64,
+    /// Smallest and largest size sampled.
+    pub size_range: (f64, f64),
+}
+
+impl CostModel {
+    /// Fits a model to `samples`. Quantities priced at fewer than two sizes
+    /// are skipped; at least one quantity needs two or more.
+    ///
+    /// For each quantity the unit price is fitted as a line in the size.
+    /// Consecutive quantities whose slopes agree within 2% share a rate, and
+    /// the cut charge is the median intercept. Breaks can only land on a
+    /// sampled quantity: the true break is anywhere after the sampled
+    /// quantity before it.
+    pub fn fit(sku: &str, store_id: &str, basis: SizeBasis, currency: &str, samples: &[PriceSample]) -> Result<CostModel, String> {
+        let mut quantities: Vec<u32> = samples.iter().map(|s| s.quantity).collect();
+        quantities.sort_unstable();
+        quantities.dedup();
+        if quantities.is_empty() {
+            return Err("no prices to fit".to_string());
+        }
+        let mut lines = Vec::with_capacity(quantities.len());
+        for &quantity in &quantities {
+            let points: Vec<(f64, f64)> = samples
+                .iter()
+                .filter(|s| s.quantity == quantity)
+                .map(|s| (s.size, s.unit_price))
+                .collect();
+            if let Some(line) = fit_line(&points) {
+                lines.push((quantity, line));
+            }
+        }
+        if lines.is_empty() {
+            return Err("need prices at two or more sizes for at least one quantity".to_string());
+        }
+
+        let mut intercepts: Vec<f64> = lines.iter().map(|(_, (intercept, _))| *intercept).collect();
+        intercepts.sort_by(f64::total_cmp);
+        let cut_charge = median(&intercepts).max(0.0);
+
+        let mut tiers: Vec<(u32, Vec<f64>)> = Vec::new();
+        for &(quantity, (_, slope)) in &lines {
+            match tiers.last_mut() {
+                Some((_, slopes)) if (slope - mean(slopes)).abs() <= BREAK_TOLERANCE * mean(slopes).abs() => slopes.push(slope),
+                _ => tiers.push((quantity, vec![slope])),
+            }
+        }
+        let breaks = tiers
+            .into_iter()
+            .map(|(min_quantity, slopes)| RateBreak { min_quantity, rate: mean(&slopes) })
+            .collect();
+
+        let mut model = CostModel {
+            sku: sku.to_string(),
+            store_id: store_id.to_string(),
+            basis,
+            cut_charge,
+            breaks,
+            currency: currency.to_string(),
+            fitted_at: Utc::now(),
+            samples: samples.len(),
+            max_error: 0.0,
+            size_range: samples.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), s| (min.min(s.size), max.max(s.size))),
+        };
+        model.max_error = samples
+            .iter()
+            .filter(|s| s.unit_price > 0.0)
+            .map(|s| (model.unit_price(s.size, s.quantity) - s.unit_price).abs() / s.unit_price)
+            .fold(0.0, f64::max);
+        Ok(model)
+    }
+
+    /// The rate for an order of `quantity` pieces.
+    pub fn rate(&self, quantity: u32) -> f64 {
+        self.breaks
+            .iter()
+            .rev()
+            .find(|b| b.min_quantity <= quantity)
+            .or(self.breaks.first())
+            .map_or(0.0, |b| b.rate)
+    }
+
+    /// Estimated price of one piece of `size` inches (or square inches).
+    pub fn unit_price(&self, size: f64, quantity: u32) -> f64 {
+        self.cut_charge + self.rate(quantity) * size
+    }
+
+    /// Estimated price of `quantity` pieces cut to `length` inches (and
+    /// `width`, for sheet and plate). `None` when the size the model needs
+    /// is missing.
+    pub fn estimate(&self, length: Option<f64>, width: Option<f64>, quantity: u32) -> Option<PriceQuote> {
+        let size = self.size(length, width)?;
+        let unit_price = self.unit_price(size, quantity);
+        Some(PriceQuote {
+            unit_price,
+            extended_price: unit_price * f64::from(quantity),
+            cut_charge: Some(self.cut_charge * f64::from(quantity)),
+            currency: self.currency.clone(),
+        })
+    }
+
+    /// Whether an estimate for this `length` and `width` lies outside the
+    /// sizes the model was fitted to, where a cut charge plus a rate may not
+    /// hold.
+    pub fn extrapolates(&self, length: Option<f64>, width: Option<f64>) -> bool {
+        let (min, max) = self.size_range;
+        self.size(length, width).is_some_and(|size| size < min - 1e-9 || size > max + 1e-9)
+    }
+
+    fn size(&self, length: Option<f64>, width: Option<f64>) -> Option<f64> {
+        match self.basis {
+            SizeBasis::Length => length,
+            SizeBasis::Area => Some(length? * width?),
+        }
+    }
+
+    /// How long ago the model was fitted.
+    pub fn age(&self) -> chrono::Duration {
+        Utc::now() - self.fitted_at
+    }
+}
+
+/// Least-squares `(intercept, slope)` through `points`, or `None` without
+/// two distinct x values.
+fn fit_line(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    if sxx <= 1e-12 {
+        return None;
+    }
+    let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let slope = sxy / sxx;
+    Some((mean_y - slope * mean_x, slope))
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Median of sorted, non-empty `values`.
+fn median(values: &[f64]) -> f64 {
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZES: [f64; 4] = [12.0, 24.0, 48.0, 96.0];
+    const QUANTITIES: [u32; 4] = [1, 5, 10, 25];
+
+    // $3.50 a cut plus $0.52/in, dropping to $0.47/in from 10 pieces.
+    fn price(size: f64, quantity: u32) -> f64 {
+        3.5 + if quantity >= 10 { 0.47 } else { 0.52 } * size
+    }
+
+    fn grid(price: impl Fn(f64, u32) -> f64) -> Vec<PriceSample> {
+        QUANTITIES
+            .iter()
+            .flat_map(|&quantity| SIZES.iter().map(move |&size| (quantity, size)))
+            .map(|(quantity, size)| PriceSample { size, quantity, unit_price: price(size, quantity) })
+            .collect()
+    }
+
+    fn fit(samples: &[PriceSample]) -> Result<CostModel, String> {
+        CostModel::fit("AF6061/3752", "023001", SizeBasis::Length, "USD", samples)
+    }
+
+    #[test]
+    fn recovers_the_cut_charge_and_a_rate_break() {
+        let model = fit(&grid(price)).unwrap();
+        assert!((model.cut_charge - 3.5).abs() < 1e-9);
+        assert_eq!(model.breaks.len(), 2);
+        assert_eq!(model.breaks[0].min_quantity, 1);
+        assert!((model.breaks[0].rate - 0.52).abs() < 1e-9);
+        assert_eq!(model.breaks[1].min_quantity, 10);
+        assert!((model.breaks[1].rate - 0.47).abs() < 1e-9);
+        assert!(model.max_error < 1e-9);
+        assert_eq!(model.samples, 16);
+        assert_eq!(model.size_range, (12.0, 96.0));
+    }
+
+    #[test]
+    fn estimates_from_the_rate_for_the_quantity() {
+        let model = fit(&grid(price)).unwrap();
+        assert!((model.unit_price(36.0, 7) - price(36.0, 7)).abs() < 1e-9);
+        // Breaks land on sampled quantities, so 9 pieces still get the 5-piece rate.
+        assert!((model.rate(9) - 0.52).abs() < 1e-9);
+        assert!((model.rate(100) - 0.47).abs() < 1e-9);
+        let quote = model.estimate(Some(36.0), None, 10).unwrap();
+        assert!((quote.extended_price - 10.0 * price(36.0, 10)).abs() < 1e-9);
+        assert!((quote.cut_charge.unwrap() - 35.0).abs() < 1e-9);
+        assert!(model.estimate(None, None, 1).is_none());
+        assert!(!model.extrapolates(Some(12.0), None) && !model.extrapolates(Some(96.0), None));
+        assert!(model.extrapolates(Some(6.0), None) && model.extrapolates(Some(144.0), None));
+    }
+
+    #[test]
+    fn noise_within_tolerance_does_not_make_breaks() {
+        // Rounded to the cent, with one length a nickel off at every quantity.
+        let noisy = grid(|size, quantity| ((price(size, quantity) + if size == 24.0 { 0.05 } else { 0.0 }) * 100.0).round() / 100.0);
+        let model = fit(&noisy).unwrap();
+        assert_eq!(model.breaks.iter().map(|b| b.min_quantity).collect::<Vec<_>>(), vec![1, 10]);
+        assert!((model.cut_charge - 3.5).abs() < 0.1);
+        assert!(model.max_error > 0.0 && model.max_error < 0.01);
+
+        let jittered = grid(|size, quantity| (3.5 + 0.52 * size) * if (quantity + size as u32).is_multiple_of(3) { 1.004 } else { 0.998 });
+        assert_eq!(fit(&jittered).unwrap().breaks.len(), 1);
+    }
+
+    #[test]
+    fn skips_quantities_priced_at_one_size() {
+        let mut samples: Vec<PriceSample> = grid(price).into_iter().filter(|s| s.quantity < 10).collect();
+        samples.push(PriceSample { size: 48.0, quantity: 10, unit_price: price(48.0, 10) });
+        let model = fit(&samples).unwrap();
+        assert_eq!(model.breaks.iter().map(|b| b.min_quantity).collect::<Vec<_>>(), vec![1]);
+        assert!((model.rate(10) - 0.52).abs() < 1e-9);
+    }
+
+    #[test]
+    fn needs_two_sizes_at_some_quantity() {
+        assert!(fit(&[]).is_err());
+        let single = [PriceSample { size: 24.0, quantity: 1, unit_price: price(24.0, 1) }];
+        assert!(fit(&single).is_err());
+        let quantities_only: Vec<PriceSample> =
+            QUANTITIES.iter().map(|&quantity| PriceSample { size: 48.0, quantity, unit_price: price(48.0, quantity) }).collect();
+        let error = fit(&quantities_only).unwrap_err();
+        assert!(error.contains("two or more sizes"), "{}", error);
+    }
+}
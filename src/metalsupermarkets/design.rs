@@ -0,0 +1,396 @@
+use super::models::{Dimensions, Material, Metal, ProductInfo, Process};
+use super::search::ProductFilter;
+use super::section::properties;
+use super::weight::weight_per_foot;
+use serde::Serialize;
+use std::cmp::Ordering;
+
+/// Strength and stiffness of an alloy, in psi.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MechanicalProperties {
+    pub yield_strength: f64,
+    pub modulus: f64,
+}
+
+// Typical minimum yield strength and elastic modulus (psi) by metal family,
+// used when the grade isn't listed below.
+const METAL_PROPERTIES: &[(Metal, f64, f64)] = &[
+    (Metal::AlloySteel, 60_000.0, 29_700_000.0),
+    (Metal::Aluminum, 15_000.0, 10_000_000.0),
+    (Metal::Brass, 40_000.0, 14_000_000.0),
+    (Metal::Bronze, 18_000.0, 14_500_000.0),
+    (Metal::Copper, 10_000.0, 17_000_000.0),
+    (Metal::MildSteel, 36_000.0, 29_000_000.0),
+    (Metal::StainlessSteel, 30_000.0, 28_000_000.0),
+    (Metal::ToolSteel, 50_000.0, 29_000_000.0),
+];
+
+// By grade (matched as a prefix) and, where it matters, process, in the
+// temper the catalog usually stocks: 6061-T6, 6063-T52, 2024-T3, 7075-T6,
+// annealed tool steel, half-hard brass.
+const GRADE_PROPERTIES: &[(Metal, &str, Option<Process>, f64, f64)] = &[
+    (Metal::Aluminum, "1100", None, 17_000.0, 10_000_000.0),
+    (Metal::Aluminum, "2011", None, 43_000.0, 10_200_000.0),
+    (Metal::Aluminum, "2024", None, 50_000.0, 10_600_000.0),
+    (Metal::Aluminum, "3003", None, 21_000.0, 10_000_000.0),
+    (Metal::Aluminum, "5052", None, 28_000.0, 10_200_000.0),
+    (Metal::Aluminum, "6061", None, 35_000.0, 10_000_000.0),
+    (Metal::Aluminum, "6063", None, 21_000.0, 10_000_000.0),
+    (Metal::Aluminum, "7075", None, 73_000.0, 10_400_000.0),
+    (Metal::AlloySteel, "4130", None, 63_000.0, 29_700_000.0),
+    (Metal::AlloySteel, "4340", None, 68_000.0, 29_700_000.0),
+    (Metal::AlloySteel, "B7", None, 105_000.0, 29_700_000.0),
+    (Metal::Brass, "C260", None, 40_000.0, 16_000_000.0),
+    (Metal::Brass, "C360", None, 45_000.0, 14_000_000.0),
+    (Metal::Bronze, "C954", None, 30_000.0, 16_000_000.0),
+    (Metal::MildSteel, "1018", Some(Process::ColdRolled), 54_000.0, 29_700_000.0),
+    (Metal::MildSteel, "1018", Some(Process::HotRolled), 32_000.0, 29_700_000.0),
+    (Metal::MildSteel, "1020", Some(Process::DrawnOverMandrel), 60_000.0, 29_700_000.0),
+    (Metal::MildSteel, "1026", Some(Process::DrawnOverMandrel), 70_000.0, 29_700_000.0),
+    (Metal::MildSteel, "1045", Some(Process::ColdRolled), 77_000.0, 29_700_000.0),
+    (Metal::MildSteel, "1045", Some(Process::HotRolled), 45_000.0, 29_700_000.0),
+    (Metal::MildSteel, "12L14", None, 60_000.0, 29_700_000.0),
+    (Metal::StainlessSteel, "303", None, 35_000.0, 28_000_000.0),
+    (Metal::StainlessSteel, "416", None, 40_000.0, 29_000_000.0),
+    (Metal::StainlessSteel, "440C", None, 65_000.0, 29_000_000.0),
+    (Metal::StainlessSteel, "17-4", None, 110_000.0, 28_500_000.0),
+    (Metal::ToolSteel, "D2", None, 60_000.0, 30_000_000.0),
+];
+
+/// Yield strength and modulus, by grade and process where known, else by
+/// metal family.
+pub fn mechanical_properties(material: &Material) -> MechanicalProperties {
+    let grade = material.grade.as_deref().unwrap_or("").to_uppercase();
+    GRADE_PROPERTIES
+        .iter()
+        .find(|(metal, prefix, process, _, _)| {
+            *metal == material.metal && grade.starts_with(prefix) && process.is_none_or(|p| material.process == Some(p))
+        })
+        .map(|(_, _, _, yield_strength, modulus)| (*yield_strength, *modulus))
+        .or_else(|| {
+            METAL_PROPERTIES
+                .iter()
+                .find(|(metal, _, _)| *metal == material.metal)
+                .map(|(_, yield_strength, modulus)| (*yield_strength, *modulus))
+        })
+        .map(|(yield_strength, modulus)| MechanicalProperties { yield_strength, modulus })
+        .expect("every metal has mechanical properties")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Support {
+    /// Pinned at both ends.
+    Simple,
+    /// Fixed at one end, free at the other.
+    Cantilever,
+    /// Fixed at both ends.
+    Fixed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadKind {
+    /// A single load at midspan, or at the free end of a cantilever.
+    Point,
+    /// The load spread evenly along the span.
+    Uniform,
+}
+
+impl std::str::FromStr for Support {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "simple" | "simply-supported" | "pinned" => Ok(Support::Simple),
+            "cantilever" => Ok(Support::Cantilever),
+            "fixed" | "fixed-fixed" => Ok(Support::Fixed),
+            _ => Err(format!("unknown support '{}' (expected simple, cantilever or fixed)", s)),
+        }
+    }
+}
+
+impl std::str::FromStr for LoadKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "point" | "concentrated" => Ok(LoadKind::Point),
+            "uniform" | "distributed" | "udl" => Ok(LoadKind::Uniform),
+            _ => Err(format!("unknown load type '{}' (expected point or uniform)", s)),
+        }
+    }
+}
+
+impl Support {
+    /// Coefficients `(m, k)` such that, for a total load `W` over span `L`,
+    /// the maximum moment is `m * W * L` and the maximum deflection is
+    /// `k * W * L³ / (E * I)`.
+    fn coefficients(self, kind: LoadKind) -> (f64, f64) {
+        match (self, kind) {
+            (Support::Simple, LoadKind::Point) => (1.0 / 4.0, 1.0 / 48.0),
+            (Support::Simple, LoadKind::Uniform) => (1.0 / 8.0, 5.0 / 384.0),
+            (Support::Cantilever, LoadKind::Point) => (1.0, 1.0 / 3.0),
+            (Support::Cantilever, LoadKind::Uniform) => (1.0 / 2.0, 1.0 / 8.0),
+            (Support::Fixed, LoadKind::Point) => (1.0 / 8.0, 1.0 / 192.0),
+            (Support::Fixed, LoadKind::Uniform) => (1.0 / 12.0, 1.0 / 384.0),
+        }
+    }
+}
+
+/// A single-span beam and its load.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BeamCase {
+    /// Span in inches.
+    pub span: f64,
+    /// Total applied load in pounds-force.
+    pub load: f64,
+    pub load_kind: LoadKind,
+    pub support: Support,
+    /// Deflection limit as a span ratio, e.g. 360 for span / 360.
+    pub deflection_limit: f64,
+    /// Yield strength divided by this gives the allowable bending stress.
+    pub safety_factor: f64,
+    /// Add the member's own weight as a uniform load.
+    pub self_weight: bool,
+}
+
+/// Bending stress and deflection of a product under a [`BeamCase`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BeamCheck {
+    /// psi
+    pub stress: f64,
+    /// Yield strength, or the lateral-torsional buckling stress of flat bar
+    /// if lower, divided by the safety factor.
+    pub allowable_stress: f64,
+    /// inches
+    pub deflection: f64,
+    pub allowable_deflection: f64,
+    /// lb/ft
+    pub weight_per_foot: f64,
+    /// Channel, beam and tee can buckle sideways too, which isn't checked;
+    /// the result only holds with the compression flange braced.
+    pub needs_bracing: bool,
+}
+
+impl BeamCheck {
+    /// The larger of the stress and deflection ratios; at most 1.0 passes.
+    pub fn utilization(&self) -> f64 {
+        (self.stress / self.allowable_stress).max(self.deflection / self.allowable_deflection)
+    }
+
+    pub fn passes(&self) -> bool {
+        self.utilization() <= 1.0
+    }
+}
+
+/// Checks `product` bending about its strong axis. `None` when its section
+/// properties or weight can't be computed, and for angles, whose section
+/// properties are about the leg axes rather than the principal axes they
+/// bend about.
+///
+/// Flat bar on edge is checked for lateral-torsional buckling over the whole
+/// span (AISC 360 F11, with Cb = 1), as if unbraced between supports.
+pub fn check_beam(product: &ProductInfo, case: &BeamCase) -> Option<BeamCheck> {
+    let material = product.material()?;
+    let dimensions = product.dimensions()?;
+    if let Dimensions::Angle { .. } = dimensions {
+        return None;
+    }
+    let section = properties(&dimensions)?;
+    let weight_per_foot = weight_per_foot(product)?;
+    let alloy = mechanical_properties(&material);
+
+    let (moment_k, deflection_k) = case.support.coefficients(case.load_kind);
+    let mut moment = moment_k * case.load * case.span;
+    let mut deflection = deflection_k * case.load * case.span.powi(3) / (alloy.modulus * section.ix);
+    if case.self_weight {
+        // For every support here, the point and uniform load maxima occur
+        // at the same place, so they add directly.
+        let own = weight_per_foot * case.span / 12.0;
+        let (moment_k, deflection_k) = case.support.coefficients(LoadKind::Uniform);
+        moment += moment_k * own * case.span;
+        deflection += deflection_k * own * case.span.powi(3) / (alloy.modulus * section.ix);
+    }
+    let critical_stress = match dimensions {
+        Dimensions::Flat { thickness, width } => {
+            let (depth, thickness) = (thickness.max(width), thickness.min(width));
+            buckling_stress(case.span * depth / (thickness * thickness), alloy)
+        }
+        _ => alloy.yield_strength,
+    };
+    Some(BeamCheck {
+        stress: moment / section.sx,
+        allowable_stress: critical_stress / case.safety_factor,
+        deflection,
+        allowable_deflection: case.span / case.deflection_limit,
+        weight_per_foot,
+        needs_bracing: matches!(dimensions, Dimensions::Channel { .. } | Dimensions::Beam { .. } | Dimensions::Tee { .. }),
+    })
+}
+
+/// Extreme fiber stress at which a rectangular bar bent about its strong
+/// axis buckles sideways, capped at yield. `slenderness` is the unbraced
+/// length times the depth over the thickness squared.
+fn buckling_stress(slenderness: f64, alloy: MechanicalProperties) -> f64 {
+    let MechanicalProperties { yield_strength, modulus } = alloy;
+    if slenderness <= 0.08 * modulus / yield_strength {
+        yield_strength
+    } else if slenderness <= 1.9 * modulus / yield_strength {
+        (yield_strength * (1.52 - 0.274 * slenderness * yield_strength / modulus)).min(yield_strength)
+    } else {
+        1.9 * modulus / slenderness
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BeamCandidate<'p, 'a> {
+    pub product: &'p ProductInfo<'a>,
+    pub check: BeamCheck,
+}
+
+/// Products matching `filter` that carry `case` within the stress and
+/// deflection limits, lightest first.
+pub fn select_beams<'p, 'a>(products: &'p [ProductInfo<'a>], filter: &ProductFilter, case: &BeamCase) -> Vec<BeamCandidate<'p, 'a>> {
+    let mut candidates: Vec<BeamCandidate> = filter
+        .apply(products)
+        .into_iter()
+        .filter_map(|product| Some(BeamCandidate { product, check: check_beam(product, case)? }))
+        .filter(|candidate| candidate.check.passes())
+        .collect();
+    candidates.sort_by(|a, b| {
+        a.check
+            .weight_per_foot
+            .partial_cmp(&b.check.weight_per_foot)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.product.sku.cmp(b.product.sku))
+    });
+    candidates
+}
+
+/// Parses a force such as `500`, `500 lb`, `200 kg` or `2 kN` into
+/// pounds-force. Bare numbers are pounds.
+pub fn parse_force(s: &str) -> Result<f64, String> {
+    let text = s.trim().to_lowercase();
+    let end = text
+        .rfind(|c: char| c.is_ascii_digit() || c == '.')
+        .map(|i| i + 1)
+        .ok_or_else(|| format!("invalid load '{}'", s))?;
+    let (number, suffix) = text.split_at(end);
+    let value: f64 = number.trim().parse().map_err(|_| format!("invalid load '{}'", s))?;
+    let pounds_per_unit = match suffix.trim() {
+        "" | "lb" | "lbs" | "lbf" => 1.0,
+        "kg" | "kgf" => 2.204_622_6,
+        "n" => 0.224_808_9,
+        "kn" => 224.808_9,
+        other => return Err(format!("unknown load unit '{}' (expected lb, kg, N or kN)", other)),
+    };
+    if !(value.is_finite() && value >= 0.0) {
+        return Err(format!("invalid load '{}'", s));
+    }
+    Ok(value * pounds_per_unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MILD_STEEL: MechanicalProperties = MechanicalProperties { yield_strength: 36_000.0, modulus: 29_000_000.0 };
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() <= expected.abs() * 1e-4, "{} is not {}", actual, expected);
+    }
+
+    fn product<'a>(description: &'a str, a: &'a str, b: &'a str, c: &'a str) -> ProductInfo<'a> {
+        ProductInfo {
+            sku: "TEST",
+            id: "1",
+            qualifier_a: a,
+            qualifier_b: b,
+            qualifier_c: c,
+            description,
+            requires_length: true,
+            requires_width: false,
+        }
+    }
+
+    fn case(span: f64, load: f64, load_kind: LoadKind, support: Support) -> BeamCase {
+        BeamCase { span, load, load_kind, support, deflection_limit: 360.0, safety_factor: 1.67, self_weight: false }
+    }
+
+    #[test]
+    fn round_bar_under_a_midspan_load() {
+        // 1" 6061 round: I = π/64 = 0.049087 in⁴, S = π/32 = 0.098175 in³.
+        // M = 100 × 24 / 4 = 600 in·lb; δ = 100 × 24³ / (48 × 10e6 × I).
+        let bar = product("ALUMINUM 6061 ROUND BAR", "1", "", "");
+        let check = check_beam(&bar, &case(24.0, 100.0, LoadKind::Point, Support::Simple)).unwrap();
+        assert_close(check.stress, 6111.5);
+        assert_close(check.deflection, 0.058671);
+        assert_close(check.allowable_stress, 35_000.0 / 1.67);
+        assert_close(check.allowable_deflection, 24.0 / 360.0);
+        assert!(check.passes());
+        assert!(!check.needs_bracing);
+    }
+
+    #[test]
+    fn cantilever_under_a_uniform_load_fails_on_deflection() {
+        // M = 100 × 24 / 2 = 1200 in·lb; δ = 100 × 24³ / (8 × 10e6 × I).
+        let bar = product("ALUMINUM 6061 ROUND BAR", "1", "", "");
+        let check = check_beam(&bar, &case(24.0, 100.0, LoadKind::Uniform, Support::Cantilever)).unwrap();
+        assert_close(check.stress, 12_223.0);
+        assert_close(check.deflection, 0.35203);
+        assert!(!check.passes());
+    }
+
+    #[test]
+    fn self_weight_adds_to_the_load() {
+        let bar = product("ALUMINUM 6061 ROUND BAR", "1", "", "");
+        let bare = case(24.0, 100.0, LoadKind::Point, Support::Simple);
+        let without = check_beam(&bar, &bare).unwrap();
+        let with = check_beam(&bar, &BeamCase { self_weight: true, ..bare }).unwrap();
+        assert!(with.stress > without.stress);
+        assert!(with.deflection > without.deflection);
+    }
+
+    #[test]
+    fn thin_flat_bar_is_limited_by_lateral_torsional_buckling() {
+        // 1/8" × 2" on edge over 48": Lb·d/t² = 48 × 2 / 0.125² = 6144, past
+        // 1.9 E/Fy = 543, so Fcr = 1.9 × 10e6 / 6144.
+        let bar = product("ALUMINUM 6061 FLAT BAR", "0.125", "2", "");
+        let check = check_beam(&bar, &case(48.0, 10.0, LoadKind::Uniform, Support::Simple)).unwrap();
+        assert_close(check.allowable_stress, 1.9 * 10_000_000.0 / 6144.0 / 1.67);
+    }
+
+    #[test]
+    fn angles_and_channels() {
+        let angle = product("ALUMINUM 6061 ANGLE", "1.5", "1.5", "0.125");
+        assert_eq!(check_beam(&angle, &case(24.0, 100.0, LoadKind::Point, Support::Simple)), None);
+        let channel = product("ALUMINUM 6061 CHANNEL", "0.5", "0.75", "0.125");
+        assert!(check_beam(&channel, &case(24.0, 100.0, LoadKind::Point, Support::Simple)).unwrap().needs_bracing);
+    }
+
+    #[test]
+    fn buckling_stress_by_slenderness() {
+        // E/Fy = 805.6: yield up to 0.08 E/Fy = 64.4, elastic past 1.9 E/Fy = 1530.6.
+        assert_eq!(buckling_stress(50.0, MILD_STEEL), 36_000.0);
+        assert_eq!(buckling_stress(500.0, MILD_STEEL), 36_000.0);
+        // 36000 × (1.52 − 0.274 × 1530 / 805.56)
+        assert_close(buckling_stress(1530.0, MILD_STEEL), 35_985.2);
+        // 1.9 × 29e6 / 3000
+        assert_close(buckling_stress(3000.0, MILD_STEEL), 18_366.7);
+    }
+
+    #[test]
+    fn parses_forces_into_pounds() {
+        assert_eq!(parse_force("500"), Ok(500.0));
+        assert_eq!(parse_force(" 500 lb "), Ok(500.0));
+        assert_eq!(parse_force("500LBS"), Ok(500.0));
+        assert_close(parse_force("200kg").unwrap(), 440.925);
+        assert_close(parse_force("2 kN").unwrap(), 449.618);
+        assert_close(parse_force("10N").unwrap(), 2.24809);
+        assert!(parse_force("-5").is_err());
+        assert!(parse_force("5 tons").is_err());
+        assert!(parse_force("lb").is_err());
+        assert!(parse_force("").is_err());
+    }
+}
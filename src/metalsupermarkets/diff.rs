@@ -0,0 +1,211 @@
+use super::catalog::ProductsJson;
+use super::models::{ProductInfo, Store};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Differences between two catalog snapshots, keyed by SKU and store ID.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CatalogDiff {
+    pub added_products: Vec<String>,
+    pub removed_products: Vec<String>,
+    pub modified_products: Vec<ProductChange>,
+    pub added_stores: Vec<Store>,
+    pub removed_stores: Vec<Store>,
+}
+
+/// A SKU present in both snapshots whose catalog entries differ.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProductChange {
+    pub sku: String,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+impl CatalogDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_products.is_empty()
+            && self.removed_products.is_empty()
+            && self.modified_products.is_empty()
+            && self.added_stores.is_empty()
+            && self.removed_stores.is_empty()
+    }
+}
+
+type FieldGetter = fn(&ProductInfo) -> String;
+
+// A SKU can be listed more than once (e.g. under two product IDs), so each
+// field is compared as the set of values across all of the SKU's entries.
+const FIELDS: &[(&str, FieldGetter)] = &[
+    ("id", |p| p.id.to_string()),
+    ("description", |p| p.description.to_string()),
+    ("qualifier_a", |p| p.qualifier_a.to_string()),
+    ("qualifier_b", |p| p.qualifier_b.to_string()),
+    ("qualifier_c", |p| p.qualifier_c.to_string()),
+    ("requires_length", |p| p.requires_length.to_string()),
+    ("requires_width", |p| p.requires_width.to_string()),
+];
+
+fn group_by_sku<'p, 'a>(products: &'p [ProductInfo<'a>]) -> BTreeMap<&'a str, Vec<&'p ProductInfo<'a>>> {
+    let mut groups: BTreeMap<&str, Vec<&ProductInfo>> = BTreeMap::new();
+    for product in products {
+        groups.entry(product.sku).or_default().push(product);
+    }
+    groups
+}
+
+fn field_values(entries: &[&ProductInfo], field: FieldGetter) -> String {
+    entries
+        .iter()
+        .map(|p| field(p))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Compares two catalog snapshots.
+pub fn diff(old: &ProductsJson, new: &ProductsJson) -> CatalogDiff {
+    let old_products = group_by_sku(&old.products);
+    let new_products = group_by_sku(&new.products);
+    let mut result = CatalogDiff::default();
+
+    for (sku, old_entries) in &old_products {
+        let Some(new_entries) = new_products.get(sku) else {
+            result.removed_products.push(sku.to_string());
+            continue;
+        };
+        let changes: Vec<FieldChange> = FIELDS
+            .iter()
+            .filter_map(|(name, field)| {
+                let old_value = field_values(old_entries, *field);
+                let new_value = field_values(new_entries, *field);
+                (old_value != new_value).then_some(FieldChange { field: name, old: old_value, new: new_value })
+            })
+            .collect();
+        if !changes.is_empty() {
+            result.modified_products.push(ProductChange { sku: sku.to_string(), changes });
+        }
+    }
+    result.added_products = new_products
+        .keys()
+        .filter(|sku| !old_products.contains_key(*sku))
+        .map(|sku| sku.to_string())
+        .collect();
+
+    let old_store_ids: BTreeSet<&str> = old.stores.iter().map(|s| s.id.as_str()).collect();
+    let new_store_ids: BTreeSet<&str> = new.stores.iter().map(|s| s.id.as_str()).collect();
+    result.added_stores = new.stores.iter().filter(|s| !old_store_ids.contains(s.id.as_str())).cloned().collect();
+    result.removed_stores = old.stores.iter().filter(|s| !new_store_ids.contains(s.id.as_str())).cloned().collect();
+    result
+}
+
+impl std::fmt::Display for CatalogDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes.");
+        }
+        writeln!(
+            f,
+            "Products: {} added, {} removed, {} modified",
+            self.added_products.len(),
+            self.removed_products.len(),
+            self.modified_products.len()
+        )?;
+        writeln!(f, "Stores: {} added, {} removed", self.added_stores.len(), self.removed_stores.len())?;
+        for sku in &self.added_products {
+            writeln!(f, "+ {}", sku)?;
+        }
+        for sku in &self.removed_products {
+            writeln!(f, "- {}", sku)?;
+        }
+        for product in &self.modified_products {
+            writeln!(f, "~ {}", product.sku)?;
+            for change in &product.changes {
+                writeln!(f, "    {}: {} -> {}", change.field, change.old, change.new)?;
+            }
+        }
+        for store in &self.added_stores {
+            writeln!(f, "+ store {} ({})", store.name, store.id)?;
+        }
+        for store in &self.removed_stores {
+            writeln!(f, "- store {} ({})", store.name, store.id)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product<'a>(sku: &'a str, id: &'a str, description: &'a str) -> ProductInfo<'a> {
+        ProductInfo {
+            sku,
+            id,
+            qualifier_a: "0.25",
+            qualifier_b: "1",
+            qualifier_c: "",
+            description,
+            requires_length: true,
+            requires_width: false,
+        }
+    }
+
+    fn store(id: &str, name: &str) -> Store {
+        Store { id: id.to_string(), name: name.to_string() }
+    }
+
+    #[test]
+    fn finds_added_removed_and_modified_products() {
+        let old = ProductsJson::new(
+            vec![
+                product("AF6061/2501", "12271", "ALUMINUM 6061 FLAT BAR"),
+                product("AF6063/2501", "12272", "ALUMINUM 6063 FLAT BAR"),
+                product("HRF/2501", "12300", "MILD STEEL HOT ROLLED FLAT BAR"),
+            ],
+            vec![store("023001", "Anaheim"), store("023002", "Irvine")],
+        );
+        let new = ProductsJson::new(
+            vec![
+                product("AF6061/2501", "12271", "ALUMINUM 6061 FLAT BAR"),
+                product("AF6063/2501", "22961", "ALUMINUM 6063 FLAT BAR"),
+                product("CRF1018/2501", "12310", "MILD STEEL COLD ROLLED 1018 FLAT BAR"),
+            ],
+            vec![store("023001", "Anaheim"), store("023003", "Long Beach")],
+        );
+        let changes = diff(&old, &new);
+        assert_eq!(changes.added_products, ["CRF1018/2501"]);
+        assert_eq!(changes.removed_products, ["HRF/2501"]);
+        assert_eq!(
+            changes.modified_products,
+            [ProductChange {
+                sku: "AF6063/2501".to_string(),
+                changes: vec![FieldChange { field: "id", old: "12272".to_string(), new: "22961".to_string() }],
+            }]
+        );
+        assert_eq!(changes.added_stores, [store("023003", "Long Beach")]);
+        assert_eq!(changes.removed_stores, [store("023002", "Irvine")]);
+    }
+
+    #[test]
+    fn a_sku_listed_twice_compares_as_a_set() {
+        let twice = vec![
+            product("AF6063/2501", "12439", "ALUMINUM 6063 FLAT BAR"),
+            product("AF6063/2501", "22961", "ALUMINUM 6063 FLAT BAR"),
+        ];
+        let reordered: Vec<ProductInfo> = twice.iter().rev().cloned().collect();
+        let changes = diff(&ProductsJson::new(twice.clone(), vec![]), &ProductsJson::new(reordered, vec![]));
+        assert!(changes.is_empty());
+
+        let once = ProductsJson::new(twice[..1].to_vec(), vec![]);
+        let changes = diff(&ProductsJson::new(twice, vec![]), &once);
+        assert_eq!(changes.modified_products[0].changes[0].old, "12439, 22961");
+        assert_eq!(changes.modified_products[0].changes[0].new, "12439");
+    }
+}
@@ -1,3 +1,14 @@
 pub mod scraper;
 pub mod models;
 pub mod api_client;
+pub mod search;
+pub mod catalog;
+pub mod diff;
+pub mod substitutes;
+pub mod units;
+pub mod section;
+pub mod weight;
+pub mod design;
+pub mod pricing;
+pub mod split;
+pub mod cost_model;
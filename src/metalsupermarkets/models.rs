@@ -1,4 +1,5 @@
 use serde::{Serialize, Deserialize};
+use super::units::Unit;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
 pub struct ProductInfo<'a> {
@@ -16,4 +17,593 @@ pub struct ProductInfo<'a> {
 pub struct Store {
 	pub id: String,
 	pub name: String,
-}
\ No newline at end of file
+}
+/// Metal family, the leading words of a product description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metal {
+	AlloySteel,
+	Aluminum,
+	Brass,
+	Bronze,
+	Copper,
+	MildSteel,
+	StainlessSteel,
+	ToolSteel,
+}
+
+/// Mill process or finish, e.g. `HOT ROLLED` or `(DOM)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Process {
+	HotRolled,
+	ColdRolled,
+	DrawnOverMandrel,
+	Welded,
+	Galvanized,
+	CommercialQuality,
+	Ground,
+	ZincPlated,
+}
+
+/// Product shape, the trailing words of a product description.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Shape {
+	Angle,
+	BarGrating,
+	Beam,
+	Channel,
+	DiamondGrip,
+	ExpandedSheet,
+	FlatBar,
+	HexagonBar,
+	PerforatedSheet,
+	Pipe,
+	Plate,
+	Rebar,
+	RectangularTube,
+	RoundBar,
+	RoundTube,
+	Sheet,
+	SquareBar,
+	SquareTube,
+	TeeBar,
+	ThreadedRod,
+	TreadPlate,
+}
+
+const METALS: &[(&str, Metal)] = &[
+	("ALLOY STEEL", Metal::AlloySteel),
+	("ALUMINUM", Metal::Aluminum),
+	("BRASS", Metal::Brass),
+	("BRONZE", Metal::Bronze),
+	("COPPER", Metal::Copper),
+	("MILD STEEL", Metal::MildSteel),
+	("STAINLESS STEEL", Metal::StainlessSteel),
+	("TOOL STEEL", Metal::ToolSteel),
+];
+
+const PROCESSES: &[(&str, Process)] = &[
+	("HOT ROLLED", Process::HotRolled),
+	("COLD ROLLED", Process::ColdRolled),
+	("(DOM)", Process::DrawnOverMandrel),
+	("STRUCTURAL (WELDED)", Process::Welded),
+	("GALVANIZED", Process::Galvanized),
+	("COMMERCIAL QUALITY", Process::CommercialQuality),
+];
+
+const SHAPES: &[(&str, Shape)] = &[
+	("ANGLE", Shape::Angle),
+	("BAR GRATING", Shape::BarGrating),
+	("BEAM", Shape::Beam),
+	("CHANNEL", Shape::Channel),
+	("DIAMOND GRIP", Shape::DiamondGrip),
+	("EXPANDED SHEET", Shape::ExpandedSheet),
+	("FLAT BAR", Shape::FlatBar),
+	("HEXAGON BAR", Shape::HexagonBar),
+	("PERFORATED SHEET", Shape::PerforatedSheet),
+	("PIPE", Shape::Pipe),
+	("PLATE", Shape::Plate),
+	("REBAR", Shape::Rebar),
+	("RECTANGULAR TUBE", Shape::RectangularTube),
+	("ROUND BAR", Shape::RoundBar),
+	("ROUND TUBE", Shape::RoundTube),
+	("SHEET", Shape::Sheet),
+	("SQUARE BAR", Shape::SquareBar),
+	("SQUARE TUBE", Shape::SquareTube),
+	("TEE BAR", Shape::TeeBar),
+	("THREADED ROD", Shape::ThreadedRod),
+	("TREAD PLATE", Shape::TreadPlate),
+];
+
+impl Metal {
+	pub const ALL: [Metal; 8] = [
+		Metal::AlloySteel,
+		Metal::Aluminum,
+		Metal::Brass,
+		Metal::Bronze,
+		Metal::Copper,
+		Metal::MildSteel,
+		Metal::StainlessSteel,
+		Metal::ToolSteel,
+	];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Metal::AlloySteel => "alloy steel",
+			Metal::Aluminum => "aluminum",
+			Metal::Brass => "brass",
+			Metal::Bronze => "bronze",
+			Metal::Copper => "copper",
+			Metal::MildSteel => "mild steel",
+			Metal::StainlessSteel => "stainless steel",
+			Metal::ToolSteel => "tool steel",
+		}
+	}
+}
+
+impl Process {
+	pub fn name(self) -> &'static str {
+		match self {
+			Process::HotRolled => "hot rolled",
+			Process::ColdRolled => "cold rolled",
+			Process::DrawnOverMandrel => "DOM",
+			Process::Welded => "welded",
+			Process::Galvanized => "galvanized",
+			Process::CommercialQuality => "commercial quality",
+			Process::Ground => "ground",
+			Process::ZincPlated => "zinc plated",
+		}
+	}
+}
+
+impl Shape {
+	pub const ALL: [Shape; 21] = [
+		Shape::Angle,
+		Shape::BarGrating,
+		Shape::Beam,
+		Shape::Channel,
+		Shape::DiamondGrip,
+		Shape::ExpandedSheet,
+		Shape::FlatBar,
+		Shape::HexagonBar,
+		Shape::PerforatedSheet,
+		Shape::Pipe,
+		Shape::Plate,
+		Shape::Rebar,
+		Shape::RectangularTube,
+		Shape::RoundBar,
+		Shape::RoundTube,
+		Shape::Sheet,
+		Shape::SquareBar,
+		Shape::SquareTube,
+		Shape::TeeBar,
+		Shape::ThreadedRod,
+		Shape::TreadPlate,
+	];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Shape::Angle => "angle",
+			Shape::BarGrating => "bar grating",
+			Shape::Beam => "beam",
+			Shape::Channel => "channel",
+			Shape::DiamondGrip => "diamond grip",
+			Shape::ExpandedSheet => "expanded sheet",
+			Shape::FlatBar => "flat bar",
+			Shape::HexagonBar => "hexagon bar",
+			Shape::PerforatedSheet => "perforated sheet",
+			Shape::Pipe => "pipe",
+			Shape::Plate => "plate",
+			Shape::Rebar => "rebar",
+			Shape::RectangularTube => "rectangular tube",
+			Shape::RoundBar => "round bar",
+			Shape::RoundTube => "round tube",
+			Shape::Sheet => "sheet",
+			Shape::SquareBar => "square bar",
+			Shape::SquareTube => "square tube",
+			Shape::TeeBar => "tee bar",
+			Shape::ThreadedRod => "threaded rod",
+			Shape::TreadPlate => "tread plate",
+		}
+	}
+}
+
+impl std::fmt::Display for Metal {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+impl std::fmt::Display for Process {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+impl std::fmt::Display for Shape {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+// Accepts "stainless steel", "stainless-steel" and "stainless_steel" alike.
+fn normalize_name(s: &str) -> String {
+	s.trim()
+		.to_lowercase()
+		.split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+		.filter(|w| !w.is_empty())
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+impl std::str::FromStr for Metal {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let name = normalize_name(s);
+		Metal::ALL
+			.into_iter()
+			.find(|m| m.name() == name)
+			.ok_or_else(|| format!("unknown metal '{}'", s))
+	}
+}
+
+impl std::str::FromStr for Shape {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let name = normalize_name(s);
+		Shape::ALL
+			.into_iter()
+			.find(|shape| shape.name() == name)
+			.ok_or_else(|| format!("unknown shape '{}'", s))
+	}
+}
+
+/// Typed decomposition of a product description such as
+/// `"MILD STEEL COLD ROLLED 1018 FLAT BAR"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Material {
+	pub metal: Metal,
+	/// Grade or alloy designation, e.g. `6061`, `304`, `C360`, `A2`.
+	pub grade: Option<String>,
+	pub process: Option<Process>,
+	pub shape: Shape,
+}
+
+impl Material {
+	/// Parses a description of the form `<METAL> [PROCESS] [GRADE] <SHAPE>`.
+	/// Returns `None` when the metal or shape is not recognised.
+	pub fn parse(description: &str) -> Option<Material> {
+		let upper = description.trim().to_uppercase();
+		let mut process = None;
+
+		// Zinc threaded rod is zinc plated mild steel, not zinc.
+		let (metal, rest) = if let Some(rest) = upper.strip_prefix("ZINC") {
+			process = Some(Process::ZincPlated);
+			(Metal::MildSteel, rest)
+		} else {
+			let (prefix, metal) = METALS.iter().find(|(prefix, _)| upper.starts_with(prefix))?;
+			(*metal, &upper[prefix.len()..])
+		};
+
+		// Ground shafting is sold as precision ground round bar.
+		let (suffix, shape) = if rest.ends_with("GROUND SHAFTING") {
+			process = Some(Process::Ground);
+			("GROUND SHAFTING", Shape::RoundBar)
+		} else {
+			*SHAPES
+				.iter()
+				.filter(|(suffix, _)| rest.ends_with(suffix))
+				.max_by_key(|(suffix, _)| suffix.len())?
+		};
+		let mut middle = rest[..rest.len() - suffix.len()].to_string();
+
+		for (keyword, p) in PROCESSES {
+			if middle.contains(keyword) {
+				process = process.or(Some(*p));
+				middle = middle.replace(keyword, " ");
+			}
+		}
+
+		let grade = middle
+			.split_whitespace()
+			.find(|token| !token.starts_with('(') && token.chars().any(|c| c.is_ascii_digit()))
+			.map(|token| token.to_string());
+
+		Some(Material { metal, grade, process, shape })
+	}
+}
+
+impl ProductInfo<'_> {
+	/// Parsed metal, grade, process and shape for this product.
+	pub fn material(&self) -> Option<Material> {
+		Material::parse(self.description)
+	}
+}
+
+/// Leading numeric value of a qualifier, e.g. `0.024` from `0.024(24Ga.)`.
+pub(crate) fn qualifier_value(qualifier: &str) -> Option<f64> {
+	let end = qualifier
+		.find(|c: char| !(c.is_ascii_digit() || c == '.'))
+		.unwrap_or(qualifier.len());
+	qualifier[..end].parse().ok()
+}
+
+// Trailing numeric value, e.g. `0.048` from `18Ga.0.048`.
+fn trailing_value(qualifier: &str) -> Option<f64> {
+	let trimmed = qualifier.trim_end_matches(')');
+	let start = trimmed
+		.rfind(|c: char| !(c.is_ascii_digit() || c == '.'))
+		.map_or(0, |i| i + 1);
+	trimmed[start..].trim_start_matches('.').parse().ok()
+}
+
+/// Shape-aware dimensions decoded from `qualifier_a`, `qualifier_b` and
+/// `qualifier_c`. All values are in inches.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Dimensions {
+	/// Flat bar.
+	Flat { thickness: f64, width: f64 },
+	/// Round bar, ground shafting, rebar and threaded rod.
+	Round { diameter: f64 },
+	Hexagon { across_flats: f64 },
+	Square { side: f64 },
+	RoundTube { outside_diameter: f64, wall: f64 },
+	/// Square and rectangular tube; `width == height` for square tube.
+	RectangularTube { width: f64, height: f64, wall: f64 },
+	Angle { leg_a: f64, leg_b: f64, thickness: f64 },
+	Channel { depth: f64, flange_width: f64, web_thickness: f64 },
+	Beam { depth: f64, flange_width: f64, web_thickness: f64 },
+	Tee { width: f64, height: f64, thickness: f64 },
+	/// Pipe by nominal size and schedule (`40`, `80`, `Standard`, `X-Heavy`, ...).
+	Pipe { nominal_size: f64, schedule: String },
+	/// Sheet, plate and tread plate.
+	Sheet { thickness: f64 },
+	Expanded { opening: f64, thickness: f64 },
+	Perforated { hole_diameter: f64, centers: f64, thickness: f64 },
+	Grating { bar_height: f64, bar_thickness: f64 },
+	/// Diamond grip plank.
+	Plank { width: f64, height: f64, thickness: f64 },
+}
+
+impl Dimensions {
+	/// Decodes the qualifiers of a product of the given shape. Returns `None`
+	/// when a qualifier the shape depends on is missing or not numeric.
+	pub fn parse(shape: Shape, a: &str, b: &str, c: &str) -> Option<Dimensions> {
+		let num = |q: &str| qualifier_value(q.trim());
+		Some(match shape {
+			Shape::FlatBar => Dimensions::Flat { thickness: num(a)?, width: num(b)? },
+			Shape::RoundBar | Shape::Rebar | Shape::ThreadedRod => Dimensions::Round { diameter: num(a)? },
+			Shape::HexagonBar => Dimensions::Hexagon { across_flats: num(a)? },
+			Shape::SquareBar => Dimensions::Square { side: num(a)? },
+			Shape::RoundTube => {
+				let (first, second) = (num(a)?, num(b)?);
+				// A few bronze bearing tubes are listed as ID x OD.
+				if second > first {
+					Dimensions::RoundTube { outside_diameter: second, wall: (second - first) / 2.0 }
+				} else {
+					Dimensions::RoundTube { outside_diameter: first, wall: second }
+				}
+			}
+			Shape::SquareTube | Shape::RectangularTube => {
+				let width = num(a)?;
+				let height = num(b).unwrap_or(width);
+				Dimensions::RectangularTube { width, height, wall: num(c)? }
+			}
+			Shape::Angle => Dimensions::Angle { leg_a: num(a)?, leg_b: num(b)?, thickness: num(c)? },
+			Shape::Channel => Dimensions::Channel { depth: num(a)?, flange_width: num(b)?, web_thickness: num(c)? },
+			Shape::Beam => Dimensions::Beam { depth: num(a)?, flange_width: num(b)?, web_thickness: num(c)? },
+			Shape::TeeBar => Dimensions::Tee { width: num(a)?, height: num(b)?, thickness: num(c)? },
+			Shape::Pipe => Dimensions::Pipe {
+				nominal_size: num(a)?,
+				schedule: b.trim().trim_start_matches("Schedule").to_string(),
+			},
+			Shape::Sheet | Shape::Plate | Shape::TreadPlate => Dimensions::Sheet { thickness: num(a)? },
+			// Steel expanded sheet gives the gauge and thickness, e.g. "#16(0.060)".
+			Shape::ExpandedSheet => Dimensions::Expanded { opening: num(a)?, thickness: trailing_value(b)? },
+			Shape::PerforatedSheet => Dimensions::Perforated {
+				hole_diameter: num(a)?,
+				centers: num(b)?,
+				thickness: trailing_value(c)?,
+			},
+			Shape::BarGrating => Dimensions::Grating { bar_height: num(a)?, bar_thickness: num(b)? },
+			Shape::DiamondGrip => Dimensions::Plank { width: num(a)?, height: num(b)?, thickness: num(c)? },
+		})
+	}
+
+	/// Named numeric dimensions, in the same order as the qualifiers they
+	/// were decoded from.
+	pub fn values(&self) -> Vec<(&'static str, f64)> {
+		match *self {
+			Dimensions::Flat { thickness, width } => vec![("thickness", thickness), ("width", width)],
+			Dimensions::Round { diameter } => vec![("diameter", diameter)],
+			Dimensions::Hexagon { across_flats } => vec![("across flats", across_flats)],
+			Dimensions::Square { side } => vec![("side", side)],
+			Dimensions::RoundTube { outside_diameter, wall } => vec![("OD", outside_diameter), ("wall", wall)],
+			Dimensions::RectangularTube { width, height, wall } => vec![("width", width), ("height", height), ("wall", wall)],
+			Dimensions::Angle { leg_a, leg_b, thickness } => vec![("leg", leg_a), ("leg", leg_b), ("thickness", thickness)],
+			Dimensions::Channel { depth, flange_width, web_thickness } | Dimensions::Beam { depth, flange_width, web_thickness } => {
+				vec![("depth", depth), ("flange", flange_width), ("web", web_thickness)]
+			}
+			Dimensions::Tee { width, height, thickness } => vec![("width", width), ("height", height), ("thickness", thickness)],
+			Dimensions::Pipe { nominal_size, .. } => vec![("NPS", nominal_size)],
+			Dimensions::Sheet { thickness } => vec![("thickness", thickness)],
+			Dimensions::Expanded { opening, thickness } => vec![("opening", opening), ("thickness", thickness)],
+			Dimensions::Perforated { hole_diameter, centers, thickness } => {
+				vec![("hole", hole_diameter), ("centers", centers), ("thickness", thickness)]
+			}
+			Dimensions::Grating { bar_height, bar_thickness } => vec![("bar height", bar_height), ("bar thickness", bar_thickness)],
+			Dimensions::Plank { width, height, thickness } => vec![("width", width), ("height", height), ("thickness", thickness)],
+		}
+	}
+
+	/// Material thickness, or wall thickness for tube. `None` for solid bar
+	/// and pipe, where it is not one of the listed dimensions.
+	pub fn thickness(&self) -> Option<f64> {
+		match *self {
+			Dimensions::Flat { thickness, .. }
+			| Dimensions::Angle { thickness, .. }
+			| Dimensions::Tee { thickness, .. }
+			| Dimensions::Sheet { thickness }
+			| Dimensions::Expanded { thickness, .. }
+			| Dimensions::Perforated { thickness, .. }
+			| Dimensions::Plank { thickness, .. } => Some(thickness),
+			Dimensions::RoundTube { wall, .. } | Dimensions::RectangularTube { wall, .. } => Some(wall),
+			Dimensions::Channel { web_thickness, .. } | Dimensions::Beam { web_thickness, .. } => Some(web_thickness),
+			Dimensions::Grating { bar_thickness, .. } => Some(bar_thickness),
+			_ => None,
+		}
+	}
+
+	/// Wall thickness of round, square and rectangular tube.
+	pub fn wall_thickness(&self) -> Option<f64> {
+		match *self {
+			Dimensions::RoundTube { wall, .. } | Dimensions::RectangularTube { wall, .. } => Some(wall),
+			_ => None,
+		}
+	}
+
+	/// Outside diameter of round bar and round tube.
+	pub fn outside_diameter(&self) -> Option<f64> {
+		match *self {
+			Dimensions::Round { diameter } => Some(diameter),
+			Dimensions::RoundTube { outside_diameter, .. } => Some(outside_diameter),
+			_ => None,
+		}
+	}
+}
+
+impl Dimensions {
+	/// Formats the dimensions in `unit`. Pipe keeps its nominal size in
+	/// inches, since NPS is a trade designation rather than a measurement.
+	pub fn to_string_in(&self, unit: Unit) -> String {
+		let parts: Vec<String> = self
+			.values()
+			.into_iter()
+			.map(|(name, value)| match self {
+				Dimensions::Pipe { .. } => format!("{}\" {}", value, name),
+				_ => format!("{} {}", unit.format(value), name),
+			})
+			.collect();
+		let mut result = parts.join(" x ");
+		if let Dimensions::Pipe { schedule, .. } = self
+			&& !schedule.is_empty()
+		{
+			if schedule.chars().all(|c| c.is_ascii_digit()) {
+				result.push_str(&format!(" SCH {}", schedule));
+			} else {
+				result.push_str(&format!(" {}", schedule));
+			}
+		}
+		result
+	}
+}
+
+impl std::fmt::Display for Dimensions {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.to_string_in(Unit::Inches))
+	}
+}
+
+impl ProductInfo<'_> {
+	/// Typed dimensions for this product, keyed off its parsed shape.
+	pub fn dimensions(&self) -> Option<Dimensions> {
+		let shape = self.material()?.shape;
+		Dimensions::parse(shape, self.qualifier_a, self.qualifier_b, self.qualifier_c)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn material(metal: Metal, grade: Option<&str>, process: Option<Process>, shape: Shape) -> Option<Material> {
+		Some(Material { metal, grade: grade.map(str::to_string), process, shape })
+	}
+
+	#[test]
+	fn parses_material_descriptions() {
+		let cases = [
+			("ALUMINUM 6061 ANGLE", material(Metal::Aluminum, Some("6061"), None, Shape::Angle)),
+			("ALUMINUM 2024T3 SHEET", material(Metal::Aluminum, Some("2024T3"), None, Shape::Sheet)),
+			("STAINLESS STEEL 304 PIPE", material(Metal::StainlessSteel, Some("304"), None, Shape::Pipe)),
+			("TOOL STEEL A2 FLAT BAR", material(Metal::ToolSteel, Some("A2"), None, Shape::FlatBar)),
+			("BRASS C260 SHEET", material(Metal::Brass, Some("C260"), None, Shape::Sheet)),
+			(
+				"MILD STEEL COLD ROLLED 12L14 HEXAGON BAR",
+				material(Metal::MildSteel, Some("12L14"), Some(Process::ColdRolled), Shape::HexagonBar),
+			),
+			(
+				"MILD STEEL 1020 (DOM) ROUND TUBE",
+				material(Metal::MildSteel, Some("1020"), Some(Process::DrawnOverMandrel), Shape::RoundTube),
+			),
+			(
+				"MILD STEEL STRUCTURAL (WELDED) RECTANGULAR TUBE",
+				material(Metal::MildSteel, None, Some(Process::Welded), Shape::RectangularTube),
+			),
+			("MILD STEEL HOT ROLLED TREAD PLATE", material(Metal::MildSteel, None, Some(Process::HotRolled), Shape::TreadPlate)),
+			(
+				"MILD STEEL COMMERCIAL QUALITY EXPANDED SHEET",
+				material(Metal::MildSteel, None, Some(Process::CommercialQuality), Shape::ExpandedSheet),
+			),
+			("MILD STEEL GALVANIZED SQUARE TUBE", material(Metal::MildSteel, None, Some(Process::Galvanized), Shape::SquareTube)),
+			("MILD STEEL (1.188 BAR GRATING", material(Metal::MildSteel, None, None, Shape::BarGrating)),
+			("ALLOY STEEL 4140 GROUND SHAFTING", material(Metal::AlloySteel, Some("4140"), Some(Process::Ground), Shape::RoundBar)),
+			("ZINC NATIONAL THREADED ROD", material(Metal::MildSteel, None, Some(Process::ZincPlated), Shape::ThreadedRod)),
+			("  aluminum 6063 channel ", material(Metal::Aluminum, Some("6063"), None, Shape::Channel)),
+			("TITANIUM GRADE 5 ROUND BAR", None),
+			("ALUMINUM 6061 WIDGET", None),
+			("", None),
+		];
+		for (description, expected) in cases {
+			assert_eq!(Material::parse(description), expected, "{}", description);
+		}
+	}
+
+	#[test]
+	fn parses_dimensions_per_shape() {
+		let pipe = |nominal_size: f64, schedule: &str| Some(Dimensions::Pipe { nominal_size, schedule: schedule.to_string() });
+		let cases = [
+			(Shape::FlatBar, ["0.25", "1", ""], Some(Dimensions::Flat { thickness: 0.25, width: 1.0 })),
+			(Shape::RoundBar, ["0.5", "Free0.500", ""], Some(Dimensions::Round { diameter: 0.5 })),
+			(Shape::Rebar, ["0.375", "HotRolled#30.375", ""], Some(Dimensions::Round { diameter: 0.375 })),
+			(Shape::ThreadedRod, ["0.25", "Coarse0.250-20", ""], Some(Dimensions::Round { diameter: 0.25 })),
+			(Shape::HexagonBar, ["0.25", "L140.250", ""], Some(Dimensions::Hexagon { across_flats: 0.25 })),
+			(Shape::SquareBar, ["0.75", "Free0.750", ""], Some(Dimensions::Square { side: 0.75 })),
+			(Shape::RoundTube, ["0.5", "0.049", ""], Some(Dimensions::RoundTube { outside_diameter: 0.5, wall: 0.049 })),
+			// Bronze bearing tube listed as ID x OD.
+			(Shape::RoundTube, ["0.5", "0.75", ""], Some(Dimensions::RoundTube { outside_diameter: 0.75, wall: 0.125 })),
+			(Shape::SquareTube, ["0.75", "", "0.065"], Some(Dimensions::RectangularTube { width: 0.75, height: 0.75, wall: 0.065 })),
+			(Shape::RectangularTube, ["1", "0.5", "0.065"], Some(Dimensions::RectangularTube { width: 1.0, height: 0.5, wall: 0.065 })),
+			(Shape::Angle, ["0.5", "0.5", "0.125"], Some(Dimensions::Angle { leg_a: 0.5, leg_b: 0.5, thickness: 0.125 })),
+			(Shape::Channel, ["3", "1.41", "0.17"], Some(Dimensions::Channel { depth: 3.0, flange_width: 1.41, web_thickness: 0.17 })),
+			(Shape::Beam, ["3", "2.33", "0.26"], Some(Dimensions::Beam { depth: 3.0, flange_width: 2.33, web_thickness: 0.26 })),
+			(Shape::TeeBar, ["1", "1", "0.125"], Some(Dimensions::Tee { width: 1.0, height: 1.0, thickness: 0.125 })),
+			(Shape::Pipe, ["0.25", "Schedule40", ""], pipe(0.25, "40")),
+			(Shape::Pipe, ["2", "Schedule80", ""], pipe(2.0, "80")),
+			(Shape::Pipe, ["1", "Standard", ""], pipe(1.0, "Standard")),
+			(Shape::Pipe, ["1.5", "X-Heavy", ""], pipe(1.5, "X-Heavy")),
+			(Shape::Sheet, ["0.016(28Ga.)", "2BMillFinish", ""], Some(Dimensions::Sheet { thickness: 0.016 })),
+			(Shape::Plate, ["0.25", "", ""], Some(Dimensions::Sheet { thickness: 0.25 })),
+			(Shape::TreadPlate, ["0.079", "HotRolled0.079", ""], Some(Dimensions::Sheet { thickness: 0.079 })),
+			(Shape::ExpandedSheet, ["0.25", "#18(0.048)", ""], Some(Dimensions::Expanded { opening: 0.25, thickness: 0.048 })),
+			(
+				Shape::PerforatedSheet,
+				["0.063", "0.094", "22Ga.0.030"],
+				Some(Dimensions::Perforated { hole_diameter: 0.063, centers: 0.094, thickness: 0.03 }),
+			),
+			(Shape::BarGrating, ["1", "0.125", ""], Some(Dimensions::Grating { bar_height: 1.0, bar_thickness: 0.125 })),
+			(Shape::DiamondGrip, ["4.75", "2", "0.08"], Some(Dimensions::Plank { width: 4.75, height: 2.0, thickness: 0.08 })),
+			(Shape::FlatBar, ["0.25", "", ""], None),
+			(Shape::Angle, ["0.5", "0.5", ""], None),
+			(Shape::RoundBar, ["Free", "", ""], None),
+		];
+		for (shape, [a, b, c], expected) in cases {
+			assert_eq!(Dimensions::parse(shape, a, b, c), expected, "{} {:?}", shape, [a, b, c]);
+		}
+	}
+}
@@ -0,0 +1,631 @@
+use super::api_client::{ApiClient, GetProductPriceParams};
+use super::catalog::{cache_dir, data_dir};
+use super::cost_model::CostModel;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::Row;
+use std::path::{Path, PathBuf};
+
+/// admin-ajax action that prices a cut piece without adding it to the cart.
+pub const PRICE_ACTION: &str = "get_product_price";
+
+// The response format isn't documented, so look for the usual names for a
+// unit and a line price, most specific first.
+const UNIT_PRICE_KEYS: &[&str] = &["unit_price", "unitprice", "price_each"];
+const EXTENDED_PRICE_KEYS: &[&str] = &["extended_price", "ext_price", "total_price", "totalprice", "subtotal", "total"];
+// Names that don't say whether the amount is for one piece or the whole
+// line; only trusted for a single piece, where the two are the same.
+const GENERIC_PRICE_KEYS: &[&str] = &["price"];
+const CUT_CHARGE_KEYS: &[&str] = &["cut_charge", "cutcharge", "cutting_charge", "cut_fee", "cut_price"];
+const CURRENCY_KEYS: &[&str] = &["currency", "currency_code"];
+const MESSAGE_KEYS: &[&str] = &["message", "error", "msg"];
+
+/// How long a cached price is used before the store is asked again.
+pub const DEFAULT_CACHE_TTL: Duration = Duration::hours(24);
+
+/// Longest slice of an unparseable body kept in [`PriceError::Unparseable`].
+const BODY_EXCERPT_CHARS: usize = 200;
+
+/// The price of one BOM line at one store.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PriceQuote {
+    /// Price of one piece.
+    pub unit_price: f64,
+    /// Price of the whole line, `unit_price` times the quantity unless the
+    /// store reports otherwise.
+    pub extended_price: f64,
+    /// Cutting charge for the line, when the store lists it separately.
+    pub cut_charge: Option<f64>,
+    /// ISO 4217 code, e.g. `USD` or `CAD`.
+    pub currency: String,
+}
+
+/// Why a line couldn't be priced.
+#[derive(Debug)]
+pub enum PriceError {
+    /// The request didn't complete.
+    Http(reqwest::Error),
+    /// The server answered with a non-success status.
+    Status(reqwest::StatusCode),
+    /// The store declined to price the line, with its message.
+    Rejected(String),
+    /// The response had no recognizable price. Holds the start of the body.
+    Unparseable(String),
+    /// The response only had an amount that may be for one piece or for the
+    /// whole line, and the line is for more than one piece.
+    Ambiguous(f64),
+}
+
+impl std::fmt::Display for PriceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PriceError::Http(e) => write!(f, "network error: {}", e),
+            PriceError::Status(status) => write!(f, "server returned {}", status),
+            PriceError::Rejected(message) => write!(f, "store declined to price it: {}", message),
+            PriceError::Unparseable(excerpt) => write!(f, "no price in response: {}", excerpt),
+            PriceError::Ambiguous(amount) => write!(f, "can't tell whether {} is the price of one piece or of the line", amount),
+        }
+    }
+}
+
+impl std::error::Error for PriceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PriceError::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for PriceError {
+    fn from(e: reqwest::Error) -> Self {
+        PriceError::Http(e)
+    }
+}
+
+impl PriceQuote {
+    /// Parses a `get_product_price` response body for a line of `quantity`
+    /// pieces. Accepts the WordPress `{"success": ..., "data": ...}` envelope
+    /// or a bare JSON object, with amounts as numbers or strings such as
+    /// `"$1,234.56"`. A bare `price`, or failing that the first dollar amount
+    /// in a plain text or HTML body, is only used for a single piece, since
+    /// it could be either the unit or the line price.
+    pub fn parse(body: &str, quantity: u32) -> Result<PriceQuote, PriceError> {
+        let pieces = quantity.max(1);
+        let quantity = f64::from(pieces);
+        let json = serde_json::from_str::<Value>(body).ok();
+        let (unit, extended, generic, cut_charge, currency) = match &json {
+            Some(json) => {
+                let data = json.get("data").unwrap_or(json);
+                if json.get("success").and_then(Value::as_bool) == Some(false) {
+                    return Err(PriceError::Rejected(message(data).unwrap_or_else(|| "no reason given".to_string())));
+                }
+                let currency = CURRENCY_KEYS
+                    .iter()
+                    .find_map(|key| find_key(data, key)?.as_str())
+                    .map(|code| code.trim().to_uppercase());
+                (
+                    find_amount(data, UNIT_PRICE_KEYS),
+                    find_amount(data, EXTENDED_PRICE_KEYS),
+                    find_amount(data, GENERIC_PRICE_KEYS),
+                    find_amount(data, CUT_CHARGE_KEYS),
+                    currency,
+                )
+            }
+            None => (None, None, first_dollar_amount(body), None, None),
+        };
+        let (unit_price, extended_price) = match (unit, extended, generic) {
+            (Some(unit), Some(extended), _) => (unit, extended),
+            (Some(unit), None, _) => (unit, unit * quantity),
+            (None, Some(extended), _) => (extended / quantity, extended),
+            (None, None, Some(amount)) if pieces == 1 => (amount, amount),
+            (None, None, Some(amount)) => return Err(PriceError::Ambiguous(amount)),
+            (None, None, None) => {
+                return Err(match json.as_ref().and_then(message) {
+                    Some(message) => PriceError::Rejected(message),
+                    None => PriceError::Unparseable(body.trim().chars().take(BODY_EXCERPT_CHARS).collect()),
+                });
+            }
+        };
+        Ok(PriceQuote {
+            unit_price,
+            extended_price,
+            cut_charge,
+            currency: currency.unwrap_or_else(|| guess_currency(body).to_string()),
+        })
+    }
+}
+
+/// Formats an amount with its currency: `$12.50` for US dollars, `C$12.50`
+/// for Canadian, else `12.50 EUR`.
+pub fn format_money(amount: f64, currency: &str) -> String {
+    // An empty sum is -0.0; don't print it as "$-0.00".
+    let amount = amount + 0.0;
+    match currency {
+        "USD" => format!("${:.2}", amount),
+        "CAD" => format!("C${:.2}", amount),
+        other => format!("{:.2} {}", amount, other),
+    }
+}
+
+/// The first of `keys` found in `value` or any object nested in it.
+fn find_amount(value: &Value, keys: &[&str]) -> Option<f64> {
+    keys.iter().find_map(|key| find_key(value, key)).and_then(parse_amount)
+}
+
+fn find_key<'v>(value: &'v Value, key: &str) -> Option<&'v Value> {
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+            .or_else(|| map.values().find_map(|v| find_key(v, key))),
+        Value::Array(items) => items.iter().find_map(|v| find_key(v, key)),
+        _ => None,
+    }
+}
+
+/// An error message from the response: a bare string payload, or a message key.
+fn message(data: &Value) -> Option<String> {
+    let text = match data {
+        Value::String(s) => Some(s.as_str()),
+        _ => MESSAGE_KEYS.iter().find_map(|key| find_key(data, key)?.as_str()),
+    }?;
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn parse_amount(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => parse_money(s),
+        _ => None,
+    }
+}
+
+/// Parses `"$1,234.56"`, `"1234.56"` or `"USD 12"` into a number.
+pub(crate) fn parse_money(s: &str) -> Option<f64> {
+    let digits: String = s
+        .trim()
+        .trim_start_matches(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+        .filter(|c| *c != ',')
+        .collect();
+    digits.parse().ok()
+}
+
+fn first_dollar_amount(body: &str) -> Option<f64> {
+    body.match_indices('$').find_map(|(i, _)| parse_money(&body[i..]))
+}
+
+/// Canadian stores show `CAD` or `C$`; everything else is priced in US dollars.
+fn guess_currency(body: &str) -> &'static str {
+    if body.contains("CAD") || body.contains("C$") { "CAD" } else { "USD" }
+}
+
+async fn open_pool(path: &Path, schema: &[&str]) -> Result<SqlitePool, sqlx::Error> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    let options = SqliteConnectOptions::new().filename(path).create_if_missing(true);
+    let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await?;
+    migrate(&pool, schema).await?;
+    Ok(pool)
+}
+
+async fn memory_pool(schema: &[&str]) -> Result<SqlitePool, sqlx::Error> {
+    let options = SqliteConnectOptions::new().in_memory(true);
+    let pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await?;
+    migrate(&pool, schema).await?;
+    Ok(pool)
+}
+
+async fn migrate(pool: &SqlitePool, schema: &[&str]) -> Result<(), sqlx::Error> {
+    for statement in schema {
+        sqlx::query(statement).execute(pool).await?;
+    }
+    Ok(())
+}
+
+/// Default location of the price cache database.
+pub fn default_cache_path() -> PathBuf {
+    cache_dir().join("prices.db")
+}
+
+const CACHE_SCHEMA: &[&str] = &["CREATE TABLE IF NOT EXISTS price_cache (
+        store_id TEXT NOT NULL,
+        pro_id TEXT NOT NULL,
+        pro_sku TEXT NOT NULL,
+        length TEXT NOT NULL,
+        width TEXT NOT NULL,
+        units TEXT NOT NULL,
+        quantity TEXT NOT NULL,
+        unit_price REAL NOT NULL,
+        extended_price REAL NOT NULL,
+        cut_charge REAL,
+        currency TEXT NOT NULL,
+        fetched_at INTEGER NOT NULL,
+        PRIMARY KEY (store_id, pro_id, pro_sku, length, width, units, quantity)
+    )"];
+
+/// Prices returned by the store, keyed by everything in the price request
+/// that affects the answer, so repeated quotes don't hit the site.
+#[derive(Debug, Clone)]
+pub struct PriceCache {
+    pool: SqlitePool,
+}
+
+impl PriceCache {
+    /// Opens (creating if needed) the price cache at `path`.
+    pub async fn open(path: &Path) -> Result<PriceCache, sqlx::Error> {
+        Ok(PriceCache { pool: open_pool(path, CACHE_SCHEMA).await? })
+    }
+
+    /// Opens an empty cache that lives only as long as the returned value.
+    pub async fn in_memory() -> Result<PriceCache, sqlx::Error> {
+        Ok(PriceCache { pool: memory_pool(CACHE_SCHEMA).await? })
+    }
+
+    /// The cached price for `params`, if it was fetched within `ttl`.
+    pub async fn get(&self, params: &GetProductPriceParams<'_>, ttl: Duration) -> Result<Option<PriceQuote>, sqlx::Error> {
+        let oldest = (Utc::now() - ttl).timestamp();
+        let row = sqlx::query(
+            "SELECT unit_price, extended_price, cut_charge, currency FROM price_cache
+             WHERE store_id = ? AND pro_id = ? AND pro_sku = ? AND length = ? AND width = ? AND units = ? AND quantity = ?
+                 AND fetched_at >= ?",
+        )
+        .bind(params.store_id)
+        .bind(params.pro_id)
+        .bind(params.pro_sku)
+        .bind(params.prolength)
+        .bind(params.prowidth.unwrap_or(""))
+        .bind(params.selunits)
+        .bind(params.selquantity)
+        .bind(oldest)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|row| PriceQuote {
+            unit_price: row.get("unit_price"),
+            extended_price: row.get("extended_price"),
+            cut_charge: row.get("cut_charge"),
+            currency: row.get("currency"),
+        }))
+    }
+
+    /// Records `quote` as the current price for `params`.
+    pub async fn put(&self, params: &GetProductPriceParams<'_>, quote: &PriceQuote) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO price_cache
+                 (store_id, pro_id, pro_sku, length, width, units, quantity, unit_price, extended_price, cut_charge, currency, fetched_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(params.store_id)
+        .bind(params.pro_id)
+        .bind(params.pro_sku)
+        .bind(params.prolength)
+        .bind(params.prowidth.unwrap_or(""))
+        .bind(params.selunits)
+        .bind(params.selquantity)
+        .bind(quote.unit_price)
+        .bind(quote.extended_price)
+        .bind(quote.cut_charge)
+        .bind(&quote.currency)
+        .bind(Utc::now().timestamp())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Default location of the price history database. Unlike the cache it
+/// can't be rebuilt, so it lives in the data directory, under a name of its
+/// own so it is never mistaken for the cache's `prices.db`.
+pub fn default_history_path() -> PathBuf {
+    data_dir().join("price_history.db")
+}
+
+const HISTORY_SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS price_history (
+        recorded_at INTEGER NOT NULL,
+        store_id TEXT NOT NULL,
+        sku TEXT NOT NULL,
+        product_id TEXT NOT NULL,
+        length REAL,
+        width REAL,
+        quantity INTEGER NOT NULL,
+        unit_price REAL NOT NULL,
+        extended_price REAL NOT NULL,
+        cut_charge REAL,
+        currency TEXT NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS price_history_sku ON price_history (sku, recorded_at)",
+    "CREATE TABLE IF NOT EXISTS cost_models (
+        sku TEXT NOT NULL,
+        store_id TEXT NOT NULL,
+        fitted_at INTEGER NOT NULL,
+        model TEXT NOT NULL,
+        PRIMARY KEY (sku, store_id)
+    )",
+];
+
+/// A price as fetched from a store, for the price history.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PriceRecord {
+    pub recorded_at: DateTime<Utc>,
+    pub store_id: String,
+    pub sku: String,
+    pub product_id: String,
+    /// Cut length in inches, if the product is cut to length.
+    pub length: Option<f64>,
+    /// Cut width in inches, for sheet and plate.
+    pub width: Option<f64>,
+    pub quantity: u32,
+    #[serde(flatten)]
+    pub quote: PriceQuote,
+}
+
+/// What one piece costs per unit of the size it was cut to, in the quote's
+/// currency.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "basis", content = "price", rename_all = "snake_case")]
+pub enum PriceRate {
+    /// Bar, tube, structural shapes and pipe, priced by length.
+    PerFoot(f64),
+    /// Sheet and plate, priced by area.
+    PerSquareFoot(f64),
+}
+
+impl PriceRate {
+    pub fn value(&self) -> f64 {
+        match *self {
+            PriceRate::PerFoot(price) | PriceRate::PerSquareFoot(price) => price,
+        }
+    }
+}
+
+impl PriceRecord {
+    /// A record of `quote`, priced now for `params`. Sizes are read back
+    /// from the site's fields, which are in inches.
+    pub fn new(params: &GetProductPriceParams, quote: &PriceQuote) -> PriceRecord {
+        PriceRecord {
+            recorded_at: Utc::now(),
+            store_id: params.store_id.to_string(),
+            sku: params.pro_sku.to_string(),
+            product_id: params.pro_id.to_string(),
+            length: params.prolength.trim().parse().ok(),
+            width: params.prowidth.and_then(|w| w.trim().parse().ok()),
+            quantity: params.selquantity.trim().parse().unwrap_or(1),
+            quote: quote.clone(),
+        }
+    }
+
+    /// The material price of one piece per foot, or per square foot when it
+    /// was cut to a width too. Cutting charges are left out so prices for
+    /// different lengths compare. `None` without a cut length.
+    pub fn rate(&self) -> Option<PriceRate> {
+        let cut_each = self.quote.cut_charge.unwrap_or(0.0) / f64::from(self.quantity.max(1));
+        let material = self.quote.unit_price - cut_each;
+        let feet = self.length? / 12.0;
+        match self.width {
+            Some(width) => Some(PriceRate::PerSquareFoot(material / (feet * width / 12.0))),
+            None => Some(PriceRate::PerFoot(material / feet)),
+        }
+    }
+}
+
+/// A price from [`PriceHistory::fetch`].
+#[derive(Debug)]
+pub struct FetchedPrice {
+    pub quote: PriceQuote,
+    /// Why the price is missing from the history, if it is.
+    pub record_error: Option<sqlx::Error>,
+}
+
+/// Every price fetched from a store, with when and where it was fetched,
+/// and the cost models fitted to them.
+#[derive(Debug, Clone)]
+pub struct PriceHistory {
+    pool: SqlitePool,
+}
+
+impl PriceHistory {
+    /// Opens (creating if needed) the price history at `path`.
+    pub async fn open(path: &Path) -> Result<PriceHistory, sqlx::Error> {
+        Ok(PriceHistory { pool: open_pool(path, HISTORY_SCHEMA).await? })
+    }
+
+    /// Opens an empty history that lives only as long as the returned value.
+    pub async fn in_memory() -> Result<PriceHistory, sqlx::Error> {
+        Ok(PriceHistory { pool: memory_pool(HISTORY_SCHEMA).await? })
+    }
+
+    /// Asks the store for a price through `api` and records it. A price
+    /// that could not be recorded is still returned, with the reason.
+    pub async fn fetch(&self, api: &ApiClient, params: &GetProductPriceParams<'_>) -> Result<FetchedPrice, PriceError> {
+        let quote = api.get_product_price(params).await?;
+        let record_error = self.record(&PriceRecord::new(params, &quote)).await.err();
+        Ok(FetchedPrice { quote, record_error })
+    }
+
+    pub async fn record(&self, record: &PriceRecord) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO price_history
+                 (recorded_at, store_id, sku, product_id, length, width, quantity, unit_price, extended_price, cut_charge, currency)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(record.recorded_at.timestamp())
+        .bind(&record.store_id)
+        .bind(&record.sku)
+        .bind(&record.product_id)
+        .bind(record.length)
+        .bind(record.width)
+        .bind(record.quantity)
+        .bind(record.quote.unit_price)
+        .bind(record.quote.extended_price)
+        .bind(record.quote.cut_charge)
+        .bind(&record.quote.currency)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Recorded prices for `sku`, at `store` if given, oldest first.
+    pub async fn for_sku(&self, sku: &str, store: Option<&str>) -> Result<Vec<PriceRecord>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT * FROM price_history WHERE sku = ?1 AND (?2 IS NULL OR store_id = ?2) ORDER BY recorded_at, rowid",
+        )
+        .bind(sku)
+        .bind(store)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|row| PriceRecord {
+                recorded_at: DateTime::from_timestamp(row.get("recorded_at"), 0).unwrap_or_default(),
+                store_id: row.get("store_id"),
+                sku: row.get("sku"),
+                product_id: row.get("product_id"),
+                length: row.get("length"),
+                width: row.get("width"),
+                quantity: row.get("quantity"),
+                quote: PriceQuote {
+                    unit_price: row.get("unit_price"),
+                    extended_price: row.get("extended_price"),
+                    cut_charge: row.get("cut_charge"),
+                    currency: row.get("currency"),
+                },
+            })
+            .collect())
+    }
+
+    /// Saves `model`, replacing any earlier model for its SKU and store.
+    pub async fn save_model(&self, model: &CostModel) -> Result<(), sqlx::Error> {
+        let json = serde_json::to_string(model).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+        sqlx::query("INSERT OR REPLACE INTO cost_models (sku, store_id, fitted_at, model) VALUES (?, ?, ?, ?)")
+            .bind(&model.sku)
+            .bind(&model.store_id)
+            .bind(model.fitted_at.timestamp())
+            .bind(json)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// The cost model for `sku` at `store`, if one has been fitted.
+    pub async fn model(&self, sku: &str, store: &str) -> Result<Option<CostModel>, sqlx::Error> {
+        let row = sqlx::query("SELECT model FROM cost_models WHERE sku = ? AND store_id = ?")
+            .bind(sku)
+            .bind(store)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|row| serde_json::from_str(row.get("model")).map_err(|e| sqlx::Error::Decode(Box::new(e))))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &str, quantity: u32) -> PriceQuote {
+        PriceQuote::parse(body, quantity).unwrap_or_else(|e| panic!("{}: {}", body, e))
+    }
+
+    #[test]
+    fn parses_the_wordpress_envelope() {
+        let body = r#"{"success":true,"data":{"unit_price":12.5,"extended_price":25,"cut_charge":2,"currency":"usd"}}"#;
+        assert_eq!(
+            parse(body, 2),
+            PriceQuote { unit_price: 12.5, extended_price: 25.0, cut_charge: Some(2.0), currency: "USD".to_string() }
+        );
+    }
+
+    #[test]
+    fn finds_prices_nested_in_the_data() {
+        let body = r#"{"success":true,"data":{"line":{"price_each":"4.00","subtotal":"12.00"}}}"#;
+        let quote = parse(body, 3);
+        assert_eq!((quote.unit_price, quote.extended_price), (4.0, 12.0));
+    }
+
+    #[test]
+    fn success_false_is_rejected_with_the_message() {
+        let body = r#"{"success":false,"data":{"message":"Length exceeds the maximum"}}"#;
+        assert!(matches!(PriceQuote::parse(body, 1), Err(PriceError::Rejected(m)) if m == "Length exceeds the maximum"));
+        let body = r#"{"success":false,"data":"Invalid length"}"#;
+        assert!(matches!(PriceQuote::parse(body, 1), Err(PriceError::Rejected(m)) if m == "Invalid length"));
+        let body = r#"{"success":false}"#;
+        assert!(matches!(PriceQuote::parse(body, 1), Err(PriceError::Rejected(m)) if m == "no reason given"));
+    }
+
+    #[test]
+    fn parses_string_amounts() {
+        let quote = parse(r#"{"data":{"unit_price":"$1,234.56","cut_charge":"$3.00"}}"#, 3);
+        assert_eq!(quote.unit_price, 1234.56);
+        assert!((quote.extended_price - 3703.68).abs() < 1e-9);
+        assert_eq!(quote.cut_charge, Some(3.0));
+        assert_eq!(parse(r#"{"unitprice":"USD 12"}"#, 1).unit_price, 12.0);
+    }
+
+    #[test]
+    fn derives_the_missing_price_from_the_quantity() {
+        let unit_only = parse(r#"{"success":true,"data":{"unitprice":10}}"#, 4);
+        assert_eq!((unit_only.unit_price, unit_only.extended_price), (10.0, 40.0));
+        let extended_only = parse(r#"{"success":true,"data":{"total":"$40.00"}}"#, 4);
+        assert_eq!((extended_only.unit_price, extended_only.extended_price), (10.0, 40.0));
+    }
+
+    #[test]
+    fn falls_back_to_the_first_dollar_amount_for_one_piece() {
+        let body = r#"<div class="price"><span>Price:</span> <b>$45.10</b> <small>was $50.00</small></div>"#;
+        assert_eq!(
+            parse(body, 1),
+            PriceQuote { unit_price: 45.1, extended_price: 45.1, cut_charge: None, currency: "USD".to_string() }
+        );
+        assert_eq!(parse("Your price: C$12.00", 1).currency, "CAD");
+    }
+
+    #[test]
+    fn generic_amounts_are_ambiguous_for_several_pieces() {
+        assert!(matches!(PriceQuote::parse("<b>$45.10</b>", 2), Err(PriceError::Ambiguous(a)) if a == 45.1));
+        assert!(matches!(PriceQuote::parse(r#"{"data":{"price":"9.00"}}"#, 3), Err(PriceError::Ambiguous(a)) if a == 9.0));
+        assert_eq!(parse(r#"{"data":{"price":"9.00"}}"#, 1).extended_price, 9.0);
+        // A specific name wins over the generic one.
+        let quote = parse(r#"{"data":{"price":"27.00","unit_price":"9.00"}}"#, 3);
+        assert_eq!((quote.unit_price, quote.extended_price), (9.0, 27.0));
+    }
+
+    #[test]
+    fn reports_bodies_without_a_price() {
+        assert!(matches!(PriceQuote::parse(r#"{"success":true,"data":{}}"#, 1), Err(PriceError::Unparseable(_))));
+        assert!(matches!(PriceQuote::parse("<html>Sorry</html>", 1), Err(PriceError::Unparseable(b)) if b == "<html>Sorry</html>"));
+        let body = r#"{"success":true,"data":{"message":"Call the store for a price"}}"#;
+        assert!(matches!(PriceQuote::parse(body, 1), Err(PriceError::Rejected(_))));
+    }
+
+    #[test]
+    fn records_take_the_line_from_the_price_fields() {
+        let params = GetProductPriceParams {
+            action: PRICE_ACTION,
+            store_id: "023001",
+            store_country: "USA",
+            pro_id: "12271",
+            pro_sku: "AF6061/3752",
+            prowidth: None,
+            prolength: "30.5",
+            selunits: "Inches",
+            selquantity: "4",
+        };
+        let quote = PriceQuote { unit_price: 20.0, extended_price: 80.0, cut_charge: Some(8.0), currency: "USD".to_string() };
+        let record = PriceRecord::new(&params, &quote);
+        assert_eq!((record.store_id.as_str(), record.sku.as_str(), record.product_id.as_str()), ("023001", "AF6061/3752", "12271"));
+        assert_eq!((record.length, record.width, record.quantity), (Some(30.5), None, 4));
+        assert_eq!(record.quote, quote);
+        // $18 of material per piece over 30.5".
+        assert!(matches!(record.rate(), Some(PriceRate::PerFoot(rate)) if (rate - 18.0 / (30.5 / 12.0)).abs() < 1e-9));
+    }
+}
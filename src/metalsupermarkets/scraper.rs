@@ -1,7 +1,7 @@
+use super::catalog::{write_products_json, ProductsJson, SOURCE_URL};
 use super::models;
-use scraper::{ElementRef, Html, Selector};
-use std::fs::File;
-use serde_json;
+use scraper::{Html, Selector};
+use std::path::Path;
 // --- Pipeline fetcher functions ---
 #[allow(dead_code)]
 pub async fn fetch_metals(category_url: &str) -> Result<std::collections::HashSet<String>, reqwest::Error> {
@@ -33,7 +33,7 @@ pub async fn fetch_metals(category_url: &str) -> Result<std::collections::HashSe
             },
             Err(e) => last_err = Some(e),
         }
-        println!("[DEBUG] fetch_metals attempt {} failed, retrying...", attempt);
+        eprintln!("[DEBUG] fetch_metals attempt {} failed, retrying...", attempt);
         tokio::time::sleep(std::time::Duration::from_secs(2)).await;
     }
     Err(last_err.unwrap())
@@ -52,7 +52,7 @@ pub async fn fetch_shapes(metal_url: String) -> Result<std::collections::HashSet
                     let selector = Selector::parse("div > a").unwrap();
                     let links: HashSet<String> = document
                         .select(&selector)
-                        .filter(|el| el.value().attr("href").map_or(false, |h| h.contains("/metals/")))
+                        .filter(|el| el.value().attr("href").is_some_and(|h| h.contains("/metals/")))
                         .filter_map(|el| el.value().attr("href").map(|h| h.to_string()))
                         .collect();
                     return Ok(links);
@@ -61,7 +61,7 @@ pub async fn fetch_shapes(metal_url: String) -> Result<std::collections::HashSet
             },
             Err(e) => last_err = Some(e),
         }
-        println!("[DEBUG] fetch_shapes attempt {} failed, retrying...", attempt);
+        eprintln!("[DEBUG] fetch_shapes attempt {} failed, retrying...", attempt);
         tokio::time::sleep(std::time::Duration::from_secs(2)).await;
     }
     Err(last_err.unwrap())
@@ -82,10 +82,10 @@ pub async fn fetch_products(shape_url: String) -> Result<std::collections::HashS
                     let mut links = HashSet::new();
                     if let Some(main) = document.select(&main_selector).next() {
                         for el in main.select(&a_selector) {
-                            if let Some(href) = el.value().attr("href") {
-                                if href.contains("/product/") {
-                                    links.insert(href.to_string());
-                                }
+                            if let Some(href) = el.value().attr("href")
+                                && href.contains("/product/")
+                            {
+                                links.insert(href.to_string());
                             }
                         }
                     }
@@ -95,7 +95,7 @@ pub async fn fetch_products(shape_url: String) -> Result<std::collections::HashS
             },
             Err(e) => last_err = Some(e),
         }
-        println!("[DEBUG] fetch_products attempt {} failed, retrying...", attempt);
+        eprintln!("[DEBUG] fetch_products attempt {} failed, retrying...", attempt);
         tokio::time::sleep(std::time::Duration::from_secs(2)).await;
     }
     Err(last_err.unwrap())
@@ -113,7 +113,7 @@ pub async fn fetch_product_skus_and_ids(product_url: String) -> Result<Vec<model
                     let mut products = Vec::new();
                     let price_selector = Selector::parse("tr").unwrap();
                     let input_selector = Selector::parse("input").unwrap();
-                    for (_i, tr) in document.select(&price_selector).enumerate() {
+                    for tr in document.select(&price_selector) {
                         // Use owned Strings for local variables
                         let mut sku = String::new();
                         let mut id = String::new();
@@ -124,19 +124,17 @@ pub async fn fetch_product_skus_and_ids(product_url: String) -> Result<Vec<model
                         let mut requires_length = true;
                         let mut requires_width = false;
                         for input in tr.select(&input_selector) {
-                            if let Some(name) = input.value().attr("name") {
-                                if let Some(val) = input.value().attr("value") {
-                                    match name {
-                                        "pro_id" => id = val.to_string(),
-                                        "pro_length" => requires_length = true,
-                                        "pro_width" => requires_width = true,
-                                        "pro_sku" => sku = val.to_string(),
-                                        "pro_size1" => qualifier_a = val.to_string(),
-                                        "pro_size2" => qualifier_b = val.to_string(),
-                                        "pro_size3" => qualifier_c = val.to_string(),
-                                        "prosize2" => description = val.to_string(),
-                                        _ => {},
-                                    }
+                            if let (Some(name), Some(val)) = (input.value().attr("name"), input.value().attr("value")) {
+                                match name {
+                                    "pro_id" => id = val.to_string(),
+                                    "pro_length" => requires_length = true,
+                                    "pro_width" => requires_width = true,
+                                    "pro_sku" => sku = val.to_string(),
+                                    "pro_size1" => qualifier_a = val.to_string(),
+                                    "pro_size2" => qualifier_b = val.to_string(),
+                                    "pro_size3" => qualifier_c = val.to_string(),
+                                    "prosize2" => description = val.to_string(),
+                                    _ => {},
                                 }
                             }
                         }
@@ -168,7 +166,7 @@ pub async fn fetch_product_skus_and_ids(product_url: String) -> Result<Vec<model
             },
             Err(e) => last_err = Some(e),
         }
-        println!("[DEBUG] fetch_product_skus_and_ids attempt {} failed, retrying...", attempt);
+        eprintln!("[DEBUG] fetch_product_skus_and_ids attempt {} failed, retrying...", attempt);
         tokio::time::sleep(std::time::Duration::from_secs(2)).await;
     }
     Err(last_err.unwrap())
@@ -181,7 +179,7 @@ async fn fetch_stores() -> Result<Vec<models::Store>, reqwest::Error> {
     let btn_selector = Selector::parse("a.mystoresetbtn").unwrap();
     let h4_selector = Selector::parse("h4 > a").unwrap();
     let location_selector = Selector::parse("div.locationlists").unwrap();
-    Ok(document.select(&location_selector).map(|div| -> Option<models::Store> {
+    Ok(document.select(&location_selector).filter_map(|div| -> Option<models::Store> {
         if let (Some(btn_element), Some(name_element)) = (div.select(&btn_selector).next(), div.select(&h4_selector).next()) {
             Some(models::Store {
                 id: btn_element.value().attr("data-storeid").unwrap().to_string(),
@@ -193,38 +191,52 @@ async fn fetch_stores() -> Result<Vec<models::Store>, reqwest::Error> {
             // this currently fails when there is no "set as my store" button
             None
         }
-    }).flatten().collect())
+    }).collect())
 }
 
 
-#[allow(dead_code)]
-pub async fn gather() {
+/// What a scrape gathered, and how many pages it could not fetch.
+pub struct Scrape {
+    pub data: ProductsJson<'static>,
+    /// Shape lists, product lists, product pages and the store finder that failed
+    pub failures: usize,
+}
+
+/// Scrapes every metal, shape and product page plus the store finder.
+/// Progress and failed pages are reported on stderr; pages that fail are
+/// skipped and counted in `Scrape::failures`.
+pub async fn scrape() -> Result<Scrape, reqwest::Error> {
     let mut products: Vec<models::ProductInfo> = Vec::new();
+    let mut failures = 0;
 
-    let metals = fetch_metals("https://www.metalsupermarkets.com/metals").await.expect("Failed to fetch metals");
+    let scraped_at = chrono::Utc::now();
+    let metals = fetch_metals(SOURCE_URL).await?;
     let total_filtered = metals.len();
     for (i, metal_url) in metals.iter().enumerate() {
-        println!("[DEBUG] Processing metal {} of {}: {}", i + 1, total_filtered, metal_url);
+        eprintln!("[DEBUG] Processing metal {} of {}: {}", i + 1, total_filtered, metal_url);
         if let Ok(shapes) = fetch_shapes(metal_url.clone()).await {
             let total_shapes = shapes.len();
             for (j, shape_url) in shapes.iter().enumerate() {
-                println!("[DEBUG]   Processing shape {} of {}: {}", j + 1, total_shapes, shape_url);
+                eprintln!("[DEBUG]   Processing shape {} of {}: {}", j + 1, total_shapes, shape_url);
                 if let Ok(product_urls) = fetch_products(shape_url.clone()).await {
                     let total_products = product_urls.len();
                     for (k, product_url) in product_urls.iter().enumerate() {
-                        println!("[DEBUG]     Processing product {} of {}: {}", k + 1, total_products, product_url);
+                        eprintln!("[DEBUG]     Processing product {} of {}: {}", k + 1, total_products, product_url);
                         if let Ok(infos) = fetch_product_skus_and_ids(product_url.clone()).await {
                             products.extend(infos)
                         } else {
-                            println!("[DEBUG]     Failed to fetch product SKUs/IDs for {}", product_url);
+                            eprintln!("[DEBUG]     Failed to fetch product SKUs/IDs for {}", product_url);
+                            failures += 1;
                         }
                     }
                 } else {
-                    println!("[DEBUG]   Failed to fetch products for shape {}", shape_url);
+                    eprintln!("[DEBUG]   Failed to fetch products for shape {}", shape_url);
+                    failures += 1;
                 }
             }
         } else {
-            println!("[DEBUG] Failed to fetch shapes for metal {}", metal_url);
+            eprintln!("[DEBUG] Failed to fetch shapes for metal {}", metal_url);
+            failures += 1;
         }
     }
 
@@ -232,16 +244,25 @@ pub async fn gather() {
     let stores = match fetch_stores().await {
         Ok(s) => s,
         Err(e) => {
-            println!("[DEBUG] Failed to fetch stores: {}", e);
+            eprintln!("[DEBUG] Failed to fetch stores: {}", e);
+            failures += 1;
             Vec::new()
         }
     };
 
-    // Write both products and stores to src/resources/products.json as an object
-    let file = File::create("src/resources/products.json").expect("Failed to create products.json");
-    let output = serde_json::json!({
-        "products": products,
-        "stores": stores
-    });
-    serde_json::to_writer_pretty(file, &output).expect("Failed to write products.json");
-}
\ No newline at end of file
+    Ok(Scrape { data: ProductsJson::new(products, stores).with_metadata(scraped_at), failures })
+}
+
+/// Scrapes the site and writes products and stores to `path`, refusing to
+/// write a partial scrape.
+pub async fn gather_into(path: &Path) {
+    let scrape = scrape().await.expect("Failed to fetch metals");
+    assert!(scrape.failures == 0, "{} pages could not be fetched; not writing a partial catalog", scrape.failures);
+    write_products_json(path, &scrape.data).expect("Failed to write products.json");
+}
+
+/// Scrapes the site into src/resources/products.json, the copy bundled at build time.
+#[allow(dead_code)]
+pub async fn gather() {
+    gather_into(Path::new("src/resources/products.json")).await;
+}
@@ -0,0 +1,412 @@
+use super::models::{qualifier_value, Metal, ProductInfo, Shape};
+use std::cmp::Ordering;
+
+/// A product matched by [`search`], along with its relevance score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit<'p, 'a> {
+    pub product: &'p ProductInfo<'a>,
+    pub score: f64,
+}
+
+// Shorthand commonly used on drawings and BOMs, expanded before matching.
+const ALIASES: &[(&str, &[&str])] = &[
+    ("al", &["aluminum"]),
+    ("alu", &["aluminum"]),
+    ("ss", &["stainless"]),
+    ("ms", &["mild"]),
+    ("crs", &["cold", "rolled"]),
+    ("hrs", &["hot", "rolled"]),
+    ("hex", &["hexagon"]),
+    ("rect", &["rectangular"]),
+    ("sq", &["square"]),
+    ("rnd", &["round"]),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Number(String, f64),
+}
+
+/// Ranked fuzzy search over `description`, `sku` and the three qualifiers.
+///
+/// Text terms match description words exactly, by prefix, or within a small
+/// edit distance, and may also match the SKU. Numeric terms (decimals such as
+/// `.125` or fractions such as `1/8`) match qualifier values numerically, so
+/// `"6061 angle 1.5 x 1.5 x .125"` finds the 1.5" x 1.5" x 0.125" angle.
+/// Results are sorted best-first; products matching no term are dropped.
+pub fn search<'p, 'a>(products: &'p [ProductInfo<'a>], query: &str) -> Vec<SearchHit<'p, 'a>> {
+    let tokens = tokenize(query);
+    if tokens.is_empty() {
+        return Vec::new();
+    }
+    let mut hits: Vec<SearchHit> = products
+        .iter()
+        .filter_map(|product| {
+            let score = score_product(product, &tokens);
+            (score > 0.0).then_some(SearchHit { product, score })
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.product.description.cmp(b.product.description))
+            .then_with(|| a.product.sku.cmp(b.product.sku))
+    });
+    hits
+}
+
+/// The metal and shape a query names, if it names exactly one of each, so
+/// the catalog can narrow the products [`search`] scores. `"al 6061 flat bar"`
+/// gives aluminum flat bar; `"angle or channel"` names no single shape.
+pub fn query_filter(query: &str) -> ProductFilter {
+    let words: Vec<String> = tokenize(query)
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(text),
+            Token::Number(..) => None,
+        })
+        .collect();
+    let phrase = format!(" {} ", words.join(" "));
+    ProductFilter {
+        metal: named_once(&phrase, Metal::ALL, Metal::name),
+        shape: named_once(&phrase, Shape::ALL, Shape::name),
+        ..Default::default()
+    }
+}
+
+// The one item whose name appears in `phrase`, ignoring names that are part
+// of a longer match ("plate" within "tread plate").
+fn named_once<T: Copy, const N: usize>(phrase: &str, all: [T; N], name: fn(T) -> &'static str) -> Option<T> {
+    let found: Vec<T> = all.into_iter().filter(|item| phrase.contains(&format!(" {} ", name(*item)))).collect();
+    let mut longest = found
+        .iter()
+        .filter(|item| !found.iter().any(|other| name(*other).len() > name(**item).len() && name(*other).contains(name(**item))));
+    match (longest.next(), longest.next()) {
+        (Some(item), None) => Some(*item),
+        _ => None,
+    }
+}
+
+fn tokenize(query: &str) -> Vec<Token> {
+    let lowered = query.to_lowercase();
+    let mut tokens = Vec::new();
+    for raw in lowered.split(|c: char| c.is_whitespace() || c == ',' || c == '*' || c == '"') {
+        // "1.5x1.5" is as common as "1.5 x 1.5"
+        for part in split_dimension_separator(raw) {
+            if part.is_empty() || part == "x" {
+                continue;
+            }
+            if let Some(value) = parse_number(part) {
+                tokens.push(Token::Number(part.to_string(), value));
+            } else if let Some((_, expansion)) = ALIASES.iter().find(|(alias, _)| *alias == part) {
+                tokens.extend(expansion.iter().map(|word| Token::Text(word.to_string())));
+            } else {
+                tokens.push(Token::Text(part.to_string()));
+            }
+        }
+    }
+    tokens
+}
+
+fn split_dimension_separator(raw: &str) -> Vec<&str> {
+    let parts: Vec<&str> = raw.split('x').collect();
+    if parts.len() > 1 && parts.iter().all(|p| p.is_empty() || parse_number(p).is_some()) {
+        parts
+    } else {
+        vec![raw]
+    }
+}
+
+/// Parses a decimal (`0.125`, `.125`) or fraction (`1/8`, `1-1/2`) into inches.
+pub(crate) fn parse_number(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+    if let Some((whole, frac)) = s.split_once('-') {
+        let whole: f64 = whole.parse().ok()?;
+        return Some(whole + parse_fraction(frac)?);
+    }
+    if s.contains('/') {
+        return parse_fraction(s);
+    }
+    if s.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return s.parse().ok();
+    }
+    None
+}
+
+fn parse_fraction(s: &str) -> Option<f64> {
+    let (num, den) = s.split_once('/')?;
+    let num: f64 = num.parse().ok()?;
+    let den: f64 = den.parse().ok()?;
+    (den != 0.0).then(|| num / den)
+}
+
+fn score_product(product: &ProductInfo, tokens: &[Token]) -> f64 {
+    let description = product.description.to_lowercase();
+    let words: Vec<&str> = description
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let sku = product.sku.to_lowercase();
+    let qualifiers = [product.qualifier_a, product.qualifier_b, product.qualifier_c].map(qualifier_value);
+    let mut used = [false; 3];
+    let mut numeric_position = 0;
+    let mut score = 0.0;
+    let mut matched = 0;
+
+    for token in tokens {
+        let token_score = match token {
+            Token::Text(text) => score_text(text, &words, &sku),
+            // Only size terms take a slot, so an alloy number such as "6061"
+            // doesn't shift "2 x 1" onto Qualifier B.
+            Token::Number(text, value) => match match_qualifier(*value, &qualifiers, &mut used, numeric_position) {
+                Some(s) => {
+                    numeric_position += 1;
+                    s
+                }
+                None => score_text(text, &words, &sku),
+            },
+        };
+        if token_score > 0.0 {
+            matched += 1;
+            score += token_score;
+        }
+    }
+    if matched == 0 {
+        return 0.0;
+    }
+    // Products that satisfy every term should outrank partial matches with
+    // a higher raw score.
+    if matched == tokens.len() {
+        score += 10.0;
+    }
+    score
+}
+
+fn match_qualifier(value: f64, qualifiers: &[Option<f64>; 3], used: &mut [bool; 3], slot: usize) -> Option<f64> {
+    let candidates = qualifiers
+        .iter()
+        .enumerate()
+        .filter(|(i, q)| !used[*i] && q.is_some_and(|q| (q - value).abs() < 0.0015));
+    let mut best = None;
+    for (i, _) in candidates {
+        // Prefer the qualifier in the same position as the term, so
+        // "2 x 1" ranks a 2 x 1 angle above a 1 x 2 one.
+        if i == slot || best.is_none() {
+            best = Some(i);
+        }
+    }
+    let i = best?;
+    used[i] = true;
+    Some(if i == slot { 3.5 } else { 3.0 })
+}
+
+fn score_text(text: &str, words: &[&str], sku: &str) -> f64 {
+    if sku == text {
+        return 20.0;
+    }
+    let mut best: f64 = 0.0;
+    for word in words {
+        let s = if *word == text {
+            3.0
+        } else if text.len() >= 2 && word.starts_with(text) {
+            2.0
+        } else if text.len() >= 4 && levenshtein(word, text) <= if text.len() >= 7 { 2 } else { 1 } {
+            1.0
+        } else {
+            0.0
+        };
+        best = best.max(s);
+    }
+    if text.len() >= 3 && sku.contains(text) {
+        best = best.max(2.5);
+    }
+    best
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// Attribute filter over the parsed catalog. Unset fields match everything.
+///
+/// `a`, `b` and `c` bounds apply to the first, second and third typed
+/// dimension of a product (see [`Dimensions::values`](super::models::Dimensions::values)), so for round tube
+/// `min_a`/`max_a` bound the outside diameter and `min_b`/`max_b` the wall.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProductFilter {
+    pub metal: Option<Metal>,
+    pub shape: Option<Shape>,
+    pub grade: Option<String>,
+    pub min_a: Option<f64>,
+    pub max_a: Option<f64>,
+    pub min_b: Option<f64>,
+    pub max_b: Option<f64>,
+    pub min_c: Option<f64>,
+    pub max_c: Option<f64>,
+}
+
+impl ProductFilter {
+    pub fn matches(&self, product: &ProductInfo) -> bool {
+        let Some(material) = product.material() else {
+            return *self == ProductFilter::default();
+        };
+        if self.metal.is_some_and(|metal| metal != material.metal) {
+            return false;
+        }
+        if self.shape.is_some_and(|shape| shape != material.shape) {
+            return false;
+        }
+        if let Some(grade) = &self.grade
+            && !material.grade.as_deref().is_some_and(|g| g.eq_ignore_ascii_case(grade))
+        {
+            return false;
+        }
+        let bounds = [(self.min_a, self.max_a), (self.min_b, self.max_b), (self.min_c, self.max_c)];
+        if bounds.iter().all(|(min, max)| min.is_none() && max.is_none()) {
+            return true;
+        }
+        let Some(dimensions) = product.dimensions() else {
+            return false;
+        };
+        let values = dimensions.values();
+        bounds.iter().enumerate().all(|(i, (min, max))| {
+            if min.is_none() && max.is_none() {
+                return true;
+            }
+            match values.get(i) {
+                Some((_, value)) => min.is_none_or(|min| *value >= min) && max.is_none_or(|max| *value <= max),
+                None => false,
+            }
+        })
+    }
+
+    pub fn apply<'p, 'a>(&self, products: &'p [ProductInfo<'a>]) -> Vec<&'p ProductInfo<'a>> {
+        products.iter().filter(|p| self.matches(p)).collect()
+    }
+}
+
+/// A stocked product at least as large as a [`nearest`] target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearestHit<'p, 'a> {
+    pub product: &'p ProductInfo<'a>,
+    /// Sum over the targeted dimensions of how far the product exceeds the
+    /// target, relative to the target: 0.0 is an exact match, 0.5 is 50% over.
+    pub oversize: f64,
+}
+
+/// Stocked products that satisfy `filter`, ranked by how little they exceed
+/// its minimum bounds.
+///
+/// The minimum bounds are the required size; for example, flat bar at least
+/// 0.3" thick and 1.75" wide is `shape: Some(Shape::FlatBar), min_a: Some(0.3),
+/// min_b: Some(1.75)`. Maximum bounds still exclude products, and products
+/// without typed dimensions are skipped.
+pub fn nearest<'p, 'a>(products: &'p [ProductInfo<'a>], filter: &ProductFilter) -> Vec<NearestHit<'p, 'a>> {
+    let targets = [filter.min_a, filter.min_b, filter.min_c];
+    let mut hits: Vec<NearestHit> = products
+        .iter()
+        .filter(|product| filter.matches(product))
+        .filter_map(|product| {
+            let values = product.dimensions()?.values();
+            let oversize = targets
+                .iter()
+                .zip(&values)
+                .filter_map(|(target, (_, value))| {
+                    let target = (*target)?;
+                    Some(if target > 0.0 { (value - target) / target } else { *value })
+                })
+                .sum();
+            Some(NearestHit { product, oversize })
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        a.oversize
+            .partial_cmp(&b.oversize)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.product.description.cmp(b.product.description))
+            .then_with(|| a.product.sku.cmp(b.product.sku))
+    });
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flat<'a>(sku: &'a str, description: &'a str, thickness: &'a str, width: &'a str) -> ProductInfo<'a> {
+        ProductInfo {
+            sku,
+            id: "12271",
+            qualifier_a: thickness,
+            qualifier_b: width,
+            qualifier_c: "",
+            description,
+            requires_length: true,
+            requires_width: false,
+        }
+    }
+
+    fn products() -> Vec<ProductInfo<'static>> {
+        vec![
+            flat("AF6061/2502", "ALUMINUM 6061 FLAT BAR", "0.25", "2"),
+            flat("AF6061/3753", "ALUMINUM 6061 FLAT BAR", "0.375", "3"),
+            flat("AF6061/5002", "ALUMINUM 6061 FLAT BAR", "0.5", "2"),
+            flat("AF6061/3752", "ALUMINUM 6061 FLAT BAR", "0.375", "2"),
+            flat("AF6061/31252", "ALUMINUM 6061 FLAT BAR", "0.3125", "2"),
+            flat("HRF/3132", "MILD STEEL HOT ROLLED FLAT BAR", "0.3125", "2"),
+        ]
+    }
+
+    fn skus<'p>(hits: &[NearestHit<'p, '_>]) -> Vec<&'p str> {
+        hits.iter().map(|hit| hit.product.sku).collect()
+    }
+
+    #[test]
+    fn ranks_by_how_little_each_size_is_exceeded() {
+        let products = products();
+        let filter = ProductFilter {
+            metal: Some(Metal::Aluminum),
+            shape: Some(Shape::FlatBar),
+            min_a: Some(0.3),
+            min_b: Some(1.75),
+            ..Default::default()
+        };
+        let hits = nearest(&products, &filter);
+        // 0.25" is too thin and the steel bar is the wrong metal.
+        assert_eq!(skus(&hits), ["AF6061/31252", "AF6061/3752", "AF6061/5002", "AF6061/3753"]);
+        // (0.375 − 0.3) / 0.3 + (2 − 1.75) / 1.75
+        assert!((hits[1].oversize - (0.25 + 1.0 / 7.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn exact_sizes_come_first_and_maximums_still_exclude() {
+        let products = products();
+        let filter = ProductFilter {
+            shape: Some(Shape::FlatBar),
+            grade: Some("6061".to_string()),
+            min_a: Some(0.375),
+            min_b: Some(2.0),
+            max_b: Some(2.5),
+            ..Default::default()
+        };
+        let hits = nearest(&products, &filter);
+        assert_eq!(skus(&hits), ["AF6061/3752", "AF6061/5002"]);
+        assert_eq!(hits[0].oversize, 0.0);
+    }
+}
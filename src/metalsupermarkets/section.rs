@@ -0,0 +1,307 @@
+use super::models::Dimensions;
+use serde::Serialize;
+use std::f64::consts::PI;
+
+/// Outside diameter and wall thickness of a pipe, in inches.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipeSize {
+    pub outside_diameter: f64,
+    pub wall: f64,
+}
+
+/// Nominal size, outside diameter, and wall thickness by schedule.
+type PipeRow = (f64, f64, &'static [(&'static str, f64)]);
+
+// ASME B36.10 / B36.19 outside diameters and wall thicknesses for the
+// nominal sizes and schedules the catalog stocks. "std", "xs" and "xxs" are
+// the Standard, X-Heavy and XX-Heavy weight classes.
+#[allow(clippy::approx_constant)] // 0.318" is a wall thickness, not 1/π
+const PIPE_SIZES: &[PipeRow] = &[
+    (0.125, 0.405, &[("10", 0.049), ("40", 0.068), ("std", 0.068), ("80", 0.095), ("xs", 0.095)]),
+    (0.25, 0.540, &[("10", 0.065), ("40", 0.088), ("std", 0.088), ("80", 0.119), ("xs", 0.119)]),
+    (0.375, 0.675, &[("10", 0.065), ("40", 0.091), ("std", 0.091), ("80", 0.126), ("xs", 0.126)]),
+    (0.5, 0.840, &[("5", 0.065), ("10", 0.083), ("40", 0.109), ("std", 0.109), ("80", 0.147), ("xs", 0.147), ("160", 0.188), ("xxs", 0.294)]),
+    (0.75, 1.050, &[("5", 0.065), ("10", 0.083), ("40", 0.113), ("std", 0.113), ("80", 0.154), ("xs", 0.154), ("160", 0.219), ("xxs", 0.308)]),
+    (1.0, 1.315, &[("5", 0.065), ("10", 0.109), ("40", 0.133), ("std", 0.133), ("80", 0.179), ("xs", 0.179), ("160", 0.250), ("xxs", 0.358)]),
+    (1.25, 1.660, &[("5", 0.065), ("10", 0.109), ("40", 0.140), ("std", 0.140), ("80", 0.191), ("xs", 0.191), ("160", 0.250), ("xxs", 0.382)]),
+    (1.5, 1.900, &[("5", 0.065), ("10", 0.109), ("40", 0.145), ("std", 0.145), ("80", 0.200), ("xs", 0.200), ("160", 0.281), ("xxs", 0.400)]),
+    (2.0, 2.375, &[("5", 0.065), ("10", 0.109), ("40", 0.154), ("std", 0.154), ("80", 0.218), ("xs", 0.218), ("160", 0.344), ("xxs", 0.436)]),
+    (2.5, 2.875, &[("5", 0.083), ("10", 0.120), ("40", 0.203), ("std", 0.203), ("80", 0.276), ("xs", 0.276), ("160", 0.375), ("xxs", 0.552)]),
+    (3.0, 3.500, &[("5", 0.083), ("10", 0.120), ("40", 0.216), ("std", 0.216), ("80", 0.300), ("xs", 0.300), ("160", 0.438), ("xxs", 0.600)]),
+    (3.5, 4.000, &[("5", 0.083), ("10", 0.120), ("40", 0.226), ("std", 0.226), ("80", 0.318), ("xs", 0.318)]),
+    (4.0, 4.500, &[("5", 0.083), ("10", 0.120), ("40", 0.237), ("std", 0.237), ("80", 0.337), ("xs", 0.337), ("120", 0.438), ("160", 0.531), ("xxs", 0.674)]),
+    (5.0, 5.563, &[("5", 0.109), ("10", 0.134), ("40", 0.258), ("std", 0.258), ("80", 0.375), ("xs", 0.375), ("120", 0.500), ("160", 0.625), ("xxs", 0.750)]),
+    (6.0, 6.625, &[("5", 0.109), ("10", 0.134), ("40", 0.280), ("std", 0.280), ("80", 0.432), ("xs", 0.432), ("120", 0.562), ("160", 0.719), ("xxs", 0.864)]),
+    (8.0, 8.625, &[("5", 0.109), ("10", 0.148), ("20", 0.250), ("40", 0.322), ("std", 0.322), ("80", 0.500), ("xs", 0.500), ("120", 0.719), ("160", 0.906), ("xxs", 0.875)]),
+    (10.0, 10.750, &[("5", 0.134), ("10", 0.165), ("20", 0.250), ("40", 0.365), ("std", 0.365), ("80", 0.594), ("xs", 0.500)]),
+    (12.0, 12.750, &[("5", 0.156), ("10", 0.180), ("20", 0.250), ("40", 0.406), ("std", 0.375), ("80", 0.688), ("xs", 0.500)]),
+    (14.0, 14.000, &[("5", 0.156), ("10", 0.250), ("20", 0.312), ("40", 0.438), ("std", 0.375), ("80", 0.750), ("xs", 0.500)]),
+    (16.0, 16.000, &[("5", 0.165), ("10", 0.250), ("20", 0.312), ("40", 0.500), ("std", 0.375), ("80", 0.844), ("xs", 0.500)]),
+    (18.0, 18.000, &[("5", 0.165), ("10", 0.250), ("20", 0.312), ("40", 0.562), ("std", 0.375), ("80", 0.938), ("xs", 0.500)]),
+    (20.0, 20.000, &[("5", 0.188), ("10", 0.250), ("20", 0.375), ("40", 0.594), ("std", 0.375), ("80", 1.031), ("xs", 0.500)]),
+    (24.0, 24.000, &[("5", 0.218), ("10", 0.250), ("20", 0.375), ("40", 0.688), ("std", 0.375), ("80", 1.219), ("xs", 0.500)]),
+];
+
+/// Looks up the outside diameter and wall of a pipe by nominal size and
+/// schedule as listed in the catalog (`"40"`, `"Standard"`, `"X-Heavy"`, ...).
+pub fn pipe_size(nominal_size: f64, schedule: &str) -> Option<PipeSize> {
+    let schedule = schedule.trim().to_lowercase();
+    let schedule = match schedule.trim_start_matches("sch").trim() {
+        "standard" | "std" => "std",
+        "x-heavy" | "xh" | "xs" | "extra heavy" => "xs",
+        "xx-heavy" | "xxh" | "xxs" => "xxs",
+        // Stainless schedules (10S, 40S) share the carbon steel walls at these sizes.
+        other => other.trim_end_matches('s'),
+    };
+    let (_, outside_diameter, walls) = PIPE_SIZES.iter().find(|(nps, _, _)| (nps - nominal_size).abs() < 1e-6)?;
+    let (_, wall) = walls.iter().find(|(name, _)| *name == schedule)?;
+    Some(PipeSize { outside_diameter: *outside_diameter, wall: *wall })
+}
+
+/// Cross-sectional area in square inches of a product sold by length.
+///
+/// Corner radii and flange tapers are ignored: channel and beam flanges are
+/// taken to be as thick as the web. Returns `None` for sheet-like products,
+/// which are sold by area (see [`effective_thickness`]), for pipe sizes not
+/// in the schedule table, for grating and diamond grip plank, and for
+/// dimensions that don't describe a real section (e.g. a wall thicker than
+/// half the width).
+pub fn area(dimensions: &Dimensions) -> Option<f64> {
+    properties(dimensions).map(|p| p.area)
+}
+
+/// Solid thickness in inches of a product sold by area, i.e. its volume per
+/// square inch of face. Perforated sheet is reduced by its open area,
+/// assuming the usual 60° staggered hole pattern.
+pub fn effective_thickness(dimensions: &Dimensions) -> Option<f64> {
+    match *dimensions {
+        Dimensions::Sheet { thickness } => Some(thickness),
+        Dimensions::Perforated { hole_diameter, centers, thickness } if centers > 0.0 => {
+            let open_area = PI / (2.0 * 3f64.sqrt()) * (hole_diameter / centers).powi(2);
+            Some(thickness * (1.0 - open_area).max(0.0))
+        }
+        _ => None,
+    }
+}
+
+fn annulus(outside_diameter: f64, wall: f64) -> f64 {
+    let inside_diameter = (outside_diameter - 2.0 * wall).max(0.0);
+    PI / 4.0 * (outside_diameter * outside_diameter - inside_diameter * inside_diameter)
+}
+
+/// Geometric properties of a cross-section about its centroidal axes, in
+/// inches. `x` is the strong axis, so `ix >= iy`. Angle and tee use the
+/// axes parallel to their legs rather than the principal axes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SectionProperties {
+    /// Area, in².
+    pub area: f64,
+    /// Moments of inertia, in⁴.
+    pub ix: f64,
+    pub iy: f64,
+    /// Elastic section moduli (I / distance to the extreme fiber), in³.
+    pub sx: f64,
+    pub sy: f64,
+    /// Radii of gyration, in.
+    pub rx: f64,
+    pub ry: f64,
+}
+
+impl SectionProperties {
+    fn new(area: f64, (ix, cx): (f64, f64), (iy, cy): (f64, f64)) -> SectionProperties {
+        let (strong, weak) = if ix >= iy { ((ix, cx), (iy, cy)) } else { ((iy, cy), (ix, cx)) };
+        SectionProperties {
+            area,
+            ix: strong.0,
+            iy: weak.0,
+            sx: strong.0 / strong.1,
+            sy: weak.0 / weak.1,
+            rx: (strong.0 / area).sqrt(),
+            ry: (weak.0 / area).sqrt(),
+        }
+    }
+}
+
+/// An axis-aligned rectangle `(x, y, width, height)` of a composite section.
+type Rect = (f64, f64, f64, f64);
+
+/// Properties of a section made of `solid` rectangles with `holes` removed.
+/// `None` if any rectangle is empty or inside out.
+fn composite(solid: &[Rect], holes: &[Rect]) -> Option<SectionProperties> {
+    if solid.iter().chain(holes).any(|(_, _, w, h)| !(*w > 0.0 && *h > 0.0)) {
+        return None;
+    }
+    let parts = solid.iter().map(|r| (r, 1.0)).chain(holes.iter().map(|r| (r, -1.0)));
+    let area: f64 = parts.clone().map(|((_, _, w, h), sign)| sign * w * h).sum();
+    let x_bar = parts.clone().map(|((x, _, w, h), sign)| sign * w * h * (x + w / 2.0)).sum::<f64>() / area;
+    let y_bar = parts.clone().map(|((_, y, w, h), sign)| sign * w * h * (y + h / 2.0)).sum::<f64>() / area;
+    let ix: f64 = parts
+        .clone()
+        .map(|((_, y, w, h), sign)| sign * (w * h.powi(3) / 12.0 + w * h * (y + h / 2.0 - y_bar).powi(2)))
+        .sum();
+    let iy: f64 = parts
+        .map(|((x, _, w, h), sign)| sign * (h * w.powi(3) / 12.0 + w * h * (x + w / 2.0 - x_bar).powi(2)))
+        .sum();
+    let extent = |lo: fn(&Rect) -> f64, hi: fn(&Rect) -> f64, bar: f64| {
+        let min = solid.iter().map(lo).fold(f64::INFINITY, f64::min);
+        let max = solid.iter().map(hi).fold(f64::NEG_INFINITY, f64::max);
+        (bar - min).max(max - bar)
+    };
+    let cy = extent(|r| r.1, |r| r.1 + r.3, y_bar);
+    let cx = extent(|r| r.0, |r| r.0 + r.2, x_bar);
+    Some(SectionProperties::new(area, (ix, cy), (iy, cx)))
+}
+
+fn hollow_round(outside_diameter: f64, wall: f64) -> Option<SectionProperties> {
+    if !(outside_diameter > 0.0 && wall > 0.0 && wall <= outside_diameter / 2.0) {
+        return None;
+    }
+    let inside_diameter = outside_diameter - 2.0 * wall;
+    let i = PI / 64.0 * (outside_diameter.powi(4) - inside_diameter.powi(4));
+    let c = outside_diameter / 2.0;
+    Some(SectionProperties::new(annulus(outside_diameter, wall), (i, c), (i, c)))
+}
+
+/// Section properties for bar, tube, structural shapes and pipe, with the
+/// same simplifications and exclusions as [`area`].
+pub fn properties(dimensions: &Dimensions) -> Option<SectionProperties> {
+    match *dimensions {
+        Dimensions::Flat { thickness, width } => composite(&[(0.0, 0.0, width, thickness)], &[]),
+        Dimensions::Square { side } => composite(&[(0.0, 0.0, side, side)], &[]),
+        Dimensions::Round { diameter } => hollow_round(diameter, diameter / 2.0),
+        Dimensions::Hexagon { across_flats } if across_flats > 0.0 => {
+            let side = across_flats / 3f64.sqrt();
+            let area = 3f64.sqrt() / 2.0 * across_flats * across_flats;
+            let i = 5.0 * 3f64.sqrt() / 16.0 * side.powi(4);
+            Some(SectionProperties::new(area, (i, across_flats / 2.0), (i, side)))
+        }
+        Dimensions::RoundTube { outside_diameter, wall } => hollow_round(outside_diameter, wall),
+        Dimensions::Pipe { nominal_size, ref schedule } => {
+            let pipe = pipe_size(nominal_size, schedule)?;
+            hollow_round(pipe.outside_diameter, pipe.wall)
+        }
+        Dimensions::RectangularTube { width, height, wall } => {
+            composite(&[(0.0, 0.0, width, height)], &[(wall, wall, width - 2.0 * wall, height - 2.0 * wall)])
+        }
+        Dimensions::Angle { leg_a, leg_b, thickness: t } => {
+            composite(&[(0.0, 0.0, t, leg_a), (t, 0.0, leg_b - t, t)], &[])
+        }
+        Dimensions::Channel { depth, flange_width, web_thickness: t } => composite(
+            &[(0.0, 0.0, t, depth), (t, 0.0, flange_width - t, t), (t, depth - t, flange_width - t, t)],
+            &[],
+        ),
+        Dimensions::Beam { depth, flange_width, web_thickness: t } => composite(
+            &[
+                (0.0, 0.0, flange_width, t),
+                (0.0, depth - t, flange_width, t),
+                ((flange_width - t) / 2.0, t, t, depth - 2.0 * t),
+            ],
+            &[],
+        ),
+        Dimensions::Tee { width, height, thickness: t } => {
+            composite(&[(0.0, height - t, width, t), ((width - t) / 2.0, 0.0, t, height - t)], &[])
+        }
+        Dimensions::Hexagon { .. }
+        | Dimensions::Sheet { .. }
+        | Dimensions::Expanded { .. }
+        | Dimensions::Perforated { .. }
+        | Dimensions::Grating { .. }
+        | Dimensions::Plank { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() <= expected * tolerance, "{} is not within {} of {}", actual, tolerance, expected);
+    }
+
+    fn props(dimensions: Dimensions) -> SectionProperties {
+        properties(&dimensions).unwrap_or_else(|| panic!("no properties for {:?}", dimensions))
+    }
+
+    #[test]
+    fn solid_bars() {
+        // 1" round: A = π/4, I = π/64, S = π/32.
+        let round = props(Dimensions::Round { diameter: 1.0 });
+        assert_near(round.area, PI / 4.0, 1e-9);
+        assert_near(round.ix, PI / 64.0, 1e-9);
+        assert_near(round.sx, PI / 32.0, 1e-9);
+        // 1/4" × 2" flat on edge: I = 0.25 × 2³ / 12.
+        let flat = props(Dimensions::Flat { thickness: 0.25, width: 2.0 });
+        assert_near(flat.ix, 0.16667, 1e-4);
+        assert_near(flat.iy, 2.0 * 0.25f64.powi(3) / 12.0, 1e-9);
+    }
+
+    #[test]
+    fn hexagon_matches_the_handbook_formulas() {
+        // A = 0.866 d², I = 0.0601 d⁴, S = 0.1203 d³ about an axis parallel to the flats.
+        let hex = props(Dimensions::Hexagon { across_flats: 1.0 });
+        assert_near(hex.area, 0.866, 1e-3);
+        assert_near(hex.ix, 0.0601, 1e-3);
+        assert_near(hex.iy, 0.0601, 1e-3);
+        assert_near(hex.sx, 0.1203, 1e-3);
+    }
+
+    #[test]
+    fn tubes() {
+        // 2" OD × 0.125" wall round tube: A 0.736 in², I 0.325 in⁴.
+        let round = props(Dimensions::RoundTube { outside_diameter: 2.0, wall: 0.125 });
+        assert_near(round.area, 0.736, 1e-3);
+        assert_near(round.ix, 0.325, 1e-3);
+        // 2" × 2" × 0.125" square tube, sharp corners: A 0.9375 in², I = (2⁴ − 1.75⁴) / 12.
+        let square = props(Dimensions::RectangularTube { width: 2.0, height: 2.0, wall: 0.125 });
+        assert_near(square.area, 0.9375, 1e-9);
+        assert_near(square.ix, 0.5518, 1e-3);
+        assert_near(square.sx, 0.5518, 1e-3);
+        assert_eq!(properties(&Dimensions::RectangularTube { width: 1.0, height: 1.0, wall: 0.6 }), None);
+    }
+
+    #[test]
+    fn angle_matches_the_published_properties() {
+        // L1-1/2 × 1-1/2 × 1/8: A 0.359 in², Ix 0.078 in⁴ about the leg axis.
+        let angle = props(Dimensions::Angle { leg_a: 1.5, leg_b: 1.5, thickness: 0.125 });
+        assert_near(angle.area, 0.359, 2e-3);
+        assert_near(angle.ix, 0.078, 1e-2);
+        assert_near(angle.iy, 0.078, 1e-2);
+    }
+
+    #[test]
+    fn channel_with_uniform_walls() {
+        // 2 × 1 × 1/8 square-corner channel: A 0.469 in²; Ix is the web
+        // 0.125 × 2³ / 12 plus two 0.875 × 0.125 flanges 0.9375" off the axis.
+        let channel = props(Dimensions::Channel { depth: 2.0, flange_width: 1.0, web_thickness: 0.125 });
+        assert_near(channel.area, 0.469, 1e-3);
+        assert_near(channel.ix, 0.2759, 1e-3);
+        assert_near(channel.sx, 0.2759, 1e-3);
+    }
+
+    #[test]
+    fn beam_is_close_to_the_published_s_shape() {
+        // S3×5.7 lists Ix 2.52 in⁴ with a 0.17" web; taking the web as thick
+        // as the 0.26" flanges adds a little.
+        let beam = props(Dimensions::Beam { depth: 3.0, flange_width: 2.33, web_thickness: 0.26 });
+        assert_near(beam.ix, 2.52, 0.05);
+        assert!(beam.ix > 2.52);
+    }
+
+    #[test]
+    fn pipe_by_schedule() {
+        // 1" Schedule 40: A 0.494 in², I 0.0873 in⁴, S 0.133 in³.
+        let schedule_40 = props(Dimensions::Pipe { nominal_size: 1.0, schedule: "40".to_string() });
+        assert_near(schedule_40.area, 0.494, 2e-3);
+        assert_near(schedule_40.ix, 0.0873, 2e-3);
+        assert_near(schedule_40.sx, 0.133, 2e-3);
+        let standard = props(Dimensions::Pipe { nominal_size: 1.0, schedule: "Standard".to_string() });
+        assert_eq!(standard, schedule_40);
+        // 1" X-Heavy: A 0.639 in², I 0.106 in⁴.
+        let extra_heavy = props(Dimensions::Pipe { nominal_size: 1.0, schedule: "X-Heavy".to_string() });
+        assert_near(extra_heavy.area, 0.639, 2e-3);
+        assert_near(extra_heavy.ix, 0.106, 5e-3);
+        assert_eq!(pipe_size(1.0, "80"), Some(PipeSize { outside_diameter: 1.315, wall: 0.179 }));
+        assert_eq!(pipe_size(1.0, "10S"), pipe_size(1.0, "10"));
+        assert_eq!(properties(&Dimensions::Pipe { nominal_size: 0.3, schedule: "40".to_string() }), None);
+    }
+}
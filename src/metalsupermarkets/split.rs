@@ -0,0 +1,137 @@
+use serde::Serialize;
+
+/// Most stores [`plan_split`] accepts; it tries every subset of them.
+pub const MAX_STORES: usize = 16;
+
+/// Which store to buy each BOM line from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SplitPlan {
+    /// Store index for each line; `None` where no store priced the line.
+    pub assignments: Vec<Option<usize>>,
+    /// Sum of the chosen line prices.
+    pub material_cost: f64,
+    /// The pickup penalty times the number of stores used.
+    pub pickup_cost: f64,
+}
+
+impl SplitPlan {
+    pub fn total(&self) -> f64 {
+        self.material_cost + self.pickup_cost
+    }
+
+    /// Indices of the stores the plan buys from, in ascending order.
+    pub fn stores(&self) -> Vec<usize> {
+        let mut stores: Vec<usize> = self.assignments.iter().flatten().copied().collect();
+        stores.sort_unstable();
+        stores.dedup();
+        stores
+    }
+
+    /// Indices of the lines bought at `store`.
+    pub fn lines_at(&self, store: usize) -> Vec<usize> {
+        (0..self.assignments.len()).filter(|&line| self.assignments[line] == Some(store)).collect()
+    }
+}
+
+/// Assigns each line to a store so the line prices plus `pickup_penalty`
+/// per store used are as low as possible. `prices[line][store]` is the
+/// line's price at that store, `None` where it couldn't be priced. Lines
+/// no store priced are left unassigned. Ties go to fewer stores.
+///
+/// # Panics
+///
+/// If there are more than [`MAX_STORES`] stores.
+pub fn plan_split(prices: &[Vec<Option<f64>>], store_count: usize, pickup_penalty: f64) -> SplitPlan {
+    assert!(store_count <= MAX_STORES, "can't split across more than {} stores", MAX_STORES);
+    let mut best: Option<(SplitPlan, u32)> = None;
+    for mask in 0..(1u32 << store_count) {
+        let Some(assignments) = assign_within(prices, mask) else { continue };
+        let material_cost: f64 = assignments
+            .iter()
+            .zip(prices)
+            .filter_map(|(store, line)| line[(*store)?])
+            .sum();
+        let plan = SplitPlan { assignments, material_cost, pickup_cost: 0.0 };
+        let used = plan.stores().len() as u32;
+        let plan = SplitPlan { pickup_cost: pickup_penalty * f64::from(used), ..plan };
+        let better = match &best {
+            None => true,
+            Some((current, current_used)) => {
+                plan.total() < current.total() - 1e-9 || (plan.total() <= current.total() + 1e-9 && used < *current_used)
+            }
+        };
+        if better {
+            best = Some((plan, used));
+        }
+    }
+    best.map(|(plan, _)| plan).expect("the full set of stores always covers every priced line")
+}
+
+/// The cheapest store in `mask` for each line, or `None` if some line that
+/// any store priced has no price within `mask`.
+fn assign_within(prices: &[Vec<Option<f64>>], mask: u32) -> Option<Vec<Option<usize>>> {
+    prices
+        .iter()
+        .map(|line| {
+            let cheapest = line
+                .iter()
+                .enumerate()
+                .filter(|(store, _)| mask & (1 << store) != 0)
+                .filter_map(|(store, price)| Some((store, (*price)?)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(store, _)| store);
+            match cheapest {
+                Some(store) => Some(Some(store)),
+                None if line.iter().all(Option::is_none) => Some(None),
+                None => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_store_takes_every_line() {
+        let prices = vec![vec![Some(10.0)], vec![Some(5.0)], vec![None]];
+        let plan = plan_split(&prices, 1, 15.0);
+        assert_eq!(plan.assignments, vec![Some(0), Some(0), None]);
+        assert_eq!(plan.material_cost, 15.0);
+        assert_eq!(plan.pickup_cost, 15.0);
+        assert_eq!(plan.stores(), vec![0]);
+    }
+
+    #[test]
+    fn splits_when_the_savings_beat_the_penalty() {
+        // Store 0 is $30 cheaper on the first line, store 1 $25 on the second.
+        let prices = vec![vec![Some(20.0), Some(50.0)], vec![Some(60.0), Some(35.0)]];
+        let plan = plan_split(&prices, 2, 10.0);
+        assert_eq!(plan.assignments, vec![Some(0), Some(1)]);
+        assert_eq!(plan.total(), 20.0 + 35.0 + 2.0 * 10.0);
+        assert_eq!(plan.lines_at(1), vec![1]);
+    }
+
+    #[test]
+    fn penalty_keeps_the_order_at_one_store() {
+        // Splitting saves $25 but costs a $30 trip.
+        let prices = vec![vec![Some(20.0), Some(50.0)], vec![Some(60.0), Some(35.0)]];
+        let plan = plan_split(&prices, 2, 30.0);
+        assert_eq!(plan.assignments, vec![Some(0), Some(0)]);
+        assert_eq!(plan.total(), 80.0 + 30.0);
+    }
+
+    #[test]
+    fn lines_only_one_store_prices_go_there() {
+        let prices = vec![vec![Some(10.0), Some(12.0)], vec![None, Some(40.0)]];
+        let plan = plan_split(&prices, 2, 5.0);
+        assert_eq!(plan.assignments, vec![Some(1), Some(1)]);
+    }
+
+    #[test]
+    fn ties_go_to_fewer_stores() {
+        let prices = vec![vec![Some(10.0), Some(10.0)], vec![Some(10.0), Some(10.0)]];
+        assert_eq!(plan_split(&prices, 2, 0.0).stores().len(), 1);
+    }
+}
@@ -0,0 +1,151 @@
+use super::models::ProductInfo;
+use super::search::{nearest, ProductFilter};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// How a [`Substitute`] relates to the product it replaces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubstituteKind {
+    /// Same metal, shape and size in a different grade or process, e.g.
+    /// 6063 for 6061 or 316 for 304.
+    OtherAlloy,
+    /// The same material in the smallest stocked size above the wanted one.
+    NextSizeUp,
+}
+
+impl std::fmt::Display for SubstituteKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SubstituteKind::OtherAlloy => "other alloy",
+            SubstituteKind::NextSizeUp => "next size up",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Substitute<'p, 'a> {
+    pub product: &'p ProductInfo<'a>,
+    pub kind: SubstituteKind,
+}
+
+// Most sizes have several next-larger candidates (wider, thicker, both);
+// listing a handful is enough to pick from.
+const MAX_NEXT_SIZES: usize = 3;
+
+/// Stocked products that could stand in for `wanted`, which need not be in
+/// `products` itself (e.g. a discontinued SKU). Other alloys come first,
+/// then next sizes up, closest first. A SKU listed under several product
+/// IDs appears once. Returns nothing if `wanted`'s description or size
+/// can't be parsed.
+pub fn substitutes<'p, 'a>(products: &'p [ProductInfo<'a>], wanted: &ProductInfo) -> Vec<Substitute<'p, 'a>> {
+    let (Some(material), Some(dimensions)) = (wanted.material(), wanted.dimensions()) else {
+        return Vec::new();
+    };
+    let wanted_values = dimensions.values();
+    let same_size = |product: &ProductInfo| {
+        product.dimensions().is_some_and(|d| {
+            let values = d.values();
+            values.len() == wanted_values.len()
+                && values.iter().zip(&wanted_values).all(|((_, a), (_, b))| (a - b).abs() < 0.0015)
+        })
+    };
+
+    let mut seen: HashSet<&str> = HashSet::from([wanted.sku]);
+    let mut result: Vec<Substitute> = products
+        .iter()
+        .filter(|product| {
+            product
+                .material()
+                .is_some_and(|m| m.metal == material.metal && m.shape == material.shape && m != material)
+        })
+        .filter(|product| same_size(product))
+        .filter(|product| seen.insert(product.sku))
+        .map(|product| Substitute { product, kind: SubstituteKind::OtherAlloy })
+        .collect();
+
+    let filter = ProductFilter {
+        metal: Some(material.metal),
+        shape: Some(material.shape),
+        grade: material.grade.clone(),
+        min_a: wanted_values.first().map(|(_, v)| *v),
+        min_b: wanted_values.get(1).map(|(_, v)| *v),
+        min_c: wanted_values.get(2).map(|(_, v)| *v),
+        ..Default::default()
+    };
+    result.extend(
+        nearest(products, &filter)
+            .into_iter()
+            .filter(|hit| hit.product.material().is_some_and(|m| m == material) && !same_size(hit.product))
+            .filter(|hit| seen.insert(hit.product.sku))
+            .take(MAX_NEXT_SIZES)
+            .map(|hit| Substitute { product: hit.product, kind: SubstituteKind::NextSizeUp }),
+    );
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn angle<'a>(id: &'a str, sku: &'a str, description: &'a str, leg: &'a str, thickness: &'a str) -> ProductInfo<'a> {
+        ProductInfo {
+            sku,
+            id,
+            qualifier_a: leg,
+            qualifier_b: leg,
+            qualifier_c: thickness,
+            description,
+            requires_length: true,
+            requires_width: false,
+        }
+    }
+
+    // The bundled catalog lists the 1.5" angles under two product IDs each.
+    fn catalog() -> Vec<ProductInfo<'static>> {
+        vec![
+            angle("12264", "AA6061/15001500125", "ALUMINUM 6061 ANGLE", "1.5", "0.125"),
+            angle("22961", "AA6061/15001500125", "ALUMINUM 6061 ANGLE", "1.5", "0.125"),
+            angle("12264", "AA6061/15001500188", "ALUMINUM 6061 ANGLE", "1.5", "0.188"),
+            angle("12439", "AA6063/15001500125", "ALUMINUM 6063 ANGLE", "1.5", "0.125"),
+            angle("22961", "AA6063/15001500125", "ALUMINUM 6063 ANGLE", "1.5", "0.125"),
+            angle("12439", "AA6063/15001500188", "ALUMINUM 6063 ANGLE", "1.5", "0.188"),
+            angle("22961", "AA6063/15001500188", "ALUMINUM 6063 ANGLE", "1.5", "0.188"),
+            angle("12439", "AA6063/15001500250", "ALUMINUM 6063 ANGLE", "1.5", "0.25"),
+            angle("22961", "AA6063/15001500250", "ALUMINUM 6063 ANGLE", "1.5", "0.25"),
+            angle("12439", "AA6063/20002000125", "ALUMINUM 6063 ANGLE", "2", "0.125"),
+            angle("12439", "AA6063/10001000125", "ALUMINUM 6063 ANGLE", "1", "0.125"),
+        ]
+    }
+
+    fn of_kind<'p>(found: &[Substitute<'p, '_>], kind: SubstituteKind) -> Vec<&'p str> {
+        found.iter().filter(|s| s.kind == kind).map(|s| s.product.sku).collect()
+    }
+
+    #[test]
+    fn other_alloy_is_the_same_size_in_another_grade() {
+        let products = catalog();
+        let wanted = angle("12439", "AA6063/15001500125", "ALUMINUM 6063 ANGLE", "1.5", "0.125");
+        let found = substitutes(&products, &wanted);
+        assert_eq!(of_kind(&found, SubstituteKind::OtherAlloy), ["AA6061/15001500125"]);
+    }
+
+    #[test]
+    fn next_sizes_up_are_larger_in_the_same_grade_without_repeats() {
+        let products = catalog();
+        let wanted = angle("12439", "AA6063/15001500125", "ALUMINUM 6063 ANGLE", "1.5", "0.125");
+        let next = of_kind(&substitutes(&products, &wanted), SubstituteKind::NextSizeUp);
+        assert_eq!(next.len(), MAX_NEXT_SIZES);
+        assert_eq!(next[0], "AA6063/15001500188");
+        assert!(next.contains(&"AA6063/15001500250"));
+        assert!(next.contains(&"AA6063/20002000125"));
+        assert!(!next.contains(&"AA6063/10001000125"));
+    }
+
+    #[test]
+    fn wanted_sku_is_never_its_own_substitute() {
+        let products = catalog();
+        let wanted = angle("22961", "AA6063/15001500125", "ALUMINUM 6063 ANGLE", "1.5", "0.125");
+        assert!(substitutes(&products, &wanted).iter().all(|s| s.product.sku != wanted.sku));
+    }
+}
@@ -0,0 +1,250 @@
+use super::search::parse_number;
+use serde::{Deserialize, Serialize};
+
+/// The site's `selunits` value for cut lengths in inches. Only this one is
+/// known to be accepted, so lengths are always sent converted to inches.
+pub const SITE_UNITS: &str = "Inches";
+
+/// A unit of length, for BOM input and display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    #[default]
+    Inches,
+    Feet,
+    Millimeters,
+    Centimeters,
+    Meters,
+}
+
+impl Unit {
+    pub const ALL: [Unit; 5] = [Unit::Inches, Unit::Feet, Unit::Millimeters, Unit::Centimeters, Unit::Meters];
+
+    /// Length of one of this unit, in inches.
+    pub fn inches(self) -> f64 {
+        match self {
+            Unit::Inches => 1.0,
+            Unit::Feet => 12.0,
+            Unit::Millimeters => 1.0 / 25.4,
+            Unit::Centimeters => 1.0 / 2.54,
+            Unit::Meters => 1.0 / 0.0254,
+        }
+    }
+
+    pub fn to_inches(self, value: f64) -> f64 {
+        value * self.inches()
+    }
+
+    pub fn from_inches(self, inches: f64) -> f64 {
+        inches / self.inches()
+    }
+
+    pub fn is_metric(self) -> bool {
+        matches!(self, Unit::Millimeters | Unit::Centimeters | Unit::Meters)
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Inches => "in",
+            Unit::Feet => "ft",
+            Unit::Millimeters => "mm",
+            Unit::Centimeters => "cm",
+            Unit::Meters => "m",
+        }
+    }
+
+    /// Formats a length given in inches in this unit, e.g. `0.25"` or `6.35 mm`.
+    pub fn format(self, inches: f64) -> String {
+        let value = self.from_inches(inches);
+        match self {
+            Unit::Inches => format!("{}\"", value),
+            Unit::Feet => format!("{}'", round_to(value, 4)),
+            Unit::Millimeters => format!("{} mm", round_to(value, 2)),
+            Unit::Centimeters => format!("{} cm", round_to(value, 3)),
+            Unit::Meters => format!("{} m", round_to(value, 4)),
+        }
+    }
+}
+
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    (value * scale).round() / scale
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+impl std::str::FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "in" | "inch" | "inches" | "\"" => Ok(Unit::Inches),
+            "ft" | "foot" | "feet" | "'" => Ok(Unit::Feet),
+            "mm" | "millimeter" | "millimeters" | "millimetre" | "millimetres" => Ok(Unit::Millimeters),
+            "cm" | "centimeter" | "centimeters" | "centimetre" | "centimetres" => Ok(Unit::Centimeters),
+            "m" | "meter" | "meters" | "metre" | "metres" => Ok(Unit::Meters),
+            _ => Err(format!("unknown unit '{}' (expected in, ft, mm, cm or m)", s)),
+        }
+    }
+}
+
+/// A length and the unit it was given in.
+///
+/// Parses decimals and fractions with an optional unit suffix (`48in`,
+/// `1200 mm`, `12 3/4`, `1-1/2"`, `.5`) and feet-and-inches (`3' 6"`,
+/// `3'-6"`, `3 ft 6 in`). A number without a suffix is in the default unit:
+/// inches for [`str::parse`], or the one given to [`Length::parse`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Length {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Length {
+    pub fn new(value: f64, unit: Unit) -> Length {
+        Length { value, unit }
+    }
+
+    /// The canonical value of this length, in inches.
+    pub fn inches(&self) -> f64 {
+        self.unit.to_inches(self.value)
+    }
+
+    /// This length converted to `unit`.
+    pub fn to(&self, unit: Unit) -> f64 {
+        unit.from_inches(self.inches())
+    }
+
+    /// Parses `s`, reading a bare number as `default_unit`. Rejects anything
+    /// that isn't a positive length.
+    pub fn parse(s: &str, default_unit: Unit) -> Result<Length, String> {
+        let text = s.trim().to_lowercase();
+        if text.is_empty() {
+            return Err("empty length".to_string());
+        }
+        let length = match split_feet(&text) {
+            Some((feet, inches)) => {
+                let feet = parse_quantity(feet).ok_or_else(|| format!("invalid length '{}'", s))?;
+                let inches = inches.trim().trim_start_matches('-').trim();
+                if inches.is_empty() {
+                    Length::new(feet, Unit::Feet)
+                } else {
+                    let (quantity, unit) = split_unit(inches)?;
+                    if unit.is_some_and(|u| u != Unit::Inches) {
+                        return Err(format!("invalid length '{}': expected inches after feet", s));
+                    }
+                    let inches = parse_quantity(quantity).ok_or_else(|| format!("invalid length '{}'", s))?;
+                    Length::new(feet * 12.0 + inches, Unit::Inches)
+                }
+            }
+            None => {
+                let (quantity, unit) = split_unit(&text)?;
+                let value = parse_quantity(quantity).ok_or_else(|| format!("invalid length '{}'", s))?;
+                Length::new(value, unit.unwrap_or(default_unit))
+            }
+        };
+        if !(length.value.is_finite() && length.value > 0.0) {
+            return Err(format!("length '{}' must be greater than zero", s));
+        }
+        Ok(length)
+    }
+}
+
+/// Splits `3' 6"` or `3 ft 6 in` into the feet and the rest, if there is a rest.
+fn split_feet(text: &str) -> Option<(&str, &str)> {
+    ["'", "feet", "foot", "ft"].iter().find_map(|marker| {
+        let (feet, rest) = text.split_once(marker)?;
+        (!rest.trim().is_empty()).then_some((feet, rest))
+    })
+}
+
+/// Splits a trailing unit such as `mm` or `"` off a quantity.
+fn split_unit(text: &str) -> Result<(&str, Option<Unit>), String> {
+    let text = text.trim();
+    let Some(end) = text.rfind(|c: char| c.is_ascii_digit() || c == '.').map(|i| i + 1) else {
+        return Err(format!("invalid length '{}'", text));
+    };
+    let (quantity, suffix) = text.split_at(end);
+    let suffix = suffix.trim();
+    if suffix.is_empty() {
+        Ok((quantity, None))
+    } else {
+        Ok((quantity, Some(suffix.parse()?)))
+    }
+}
+
+/// A decimal, a fraction, or a whole number and a fraction (`12 3/4`, `1-1/2`).
+fn parse_quantity(text: &str) -> Option<f64> {
+    let text = text.trim();
+    match text.split_once(char::is_whitespace) {
+        Some((whole, fraction)) if fraction.contains('/') => {
+            Some(whole.parse::<f64>().ok()? + parse_number(fraction.trim())?)
+        }
+        Some(_) => None,
+        None => parse_number(text),
+    }
+}
+
+impl std::str::FromStr for Length {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Length::parse(s, Unit::Inches)
+    }
+}
+
+impl std::fmt::Display for Length {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.unit.format(self.inches()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lengths_to_inches() {
+        let cases = [
+            ("12 3/4", 12.75),
+            ("3' 6\"", 42.0),
+            ("3'-6\"", 42.0),
+            ("3 ft 6 in", 42.0),
+            ("6' 3-1/2\"", 75.5),
+            ("6' 3 1/2\"", 75.5),
+            ("4'", 48.0),
+            ("48in", 48.0),
+            ("48", 48.0),
+            ("1-1/2\"", 1.5),
+            ("3/4", 0.75),
+            (".5", 0.5),
+            ("0.125 in", 0.125),
+            ("1200mm", 1200.0 / 25.4),
+            ("1200 mm", 1200.0 / 25.4),
+            ("30.5 cm", 305.0 / 25.4),
+            ("1.2 m", 1200.0 / 25.4),
+        ];
+        for (text, inches) in cases {
+            let length = Length::parse(text, Unit::Inches).unwrap_or_else(|e| panic!("{}: {}", text, e));
+            assert!((length.inches() - inches).abs() < 1e-9, "{} parsed as {} in, expected {}", text, length.inches(), inches);
+        }
+    }
+
+    #[test]
+    fn bare_numbers_use_the_default_unit() {
+        assert_eq!(Length::parse("600", Unit::Millimeters), Ok(Length::new(600.0, Unit::Millimeters)));
+        assert_eq!(Length::parse("2 1/2", Unit::Feet), Ok(Length::new(2.5, Unit::Feet)));
+        assert_eq!(Length::parse("24in", Unit::Millimeters), Ok(Length::new(24.0, Unit::Inches)));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        for text in ["", "abc", "0", "-5", "12 furlongs", "3' 6 mm", "1/0", "12 34", "in"] {
+            assert!(Length::parse(text, Unit::Inches).is_err(), "{} should not parse", text);
+        }
+    }
+}
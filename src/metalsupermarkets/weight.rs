@@ -0,0 +1,98 @@
+use super::models::{Material, Metal, ProductInfo};
+use super::section::{area, effective_thickness};
+use super::units::Length;
+use serde::Serialize;
+
+pub const KG_PER_LB: f64 = 0.453_592_37;
+
+// Typical densities in lb/in³, by metal family.
+const METAL_DENSITIES: &[(Metal, f64)] = &[
+    (Metal::AlloySteel, 0.2836),
+    (Metal::Aluminum, 0.0975),
+    (Metal::Brass, 0.307),
+    (Metal::Bronze, 0.320),
+    (Metal::Copper, 0.323),
+    (Metal::MildSteel, 0.2836),
+    (Metal::StainlessSteel, 0.289),
+    (Metal::ToolSteel, 0.284),
+];
+
+// Grades whose density differs noticeably from their family's. Matched as a
+// prefix, so "2024" also covers "2024T3".
+const GRADE_DENSITIES: &[(Metal, &str, f64)] = &[
+    (Metal::Aluminum, "1100", 0.098),
+    (Metal::Aluminum, "2011", 0.102),
+    (Metal::Aluminum, "2024", 0.100),
+    (Metal::Aluminum, "3003", 0.0986),
+    (Metal::Aluminum, "5052", 0.0968),
+    (Metal::Aluminum, "7075", 0.101),
+    (Metal::Brass, "C260", 0.308),
+    (Metal::Bronze, "C932", 0.322),
+    (Metal::Bronze, "C954", 0.269),
+    (Metal::StainlessSteel, "316", 0.290),
+    (Metal::StainlessSteel, "416", 0.280),
+    (Metal::StainlessSteel, "440C", 0.280),
+    (Metal::StainlessSteel, "17-4", 0.282),
+    (Metal::ToolSteel, "D2", 0.278),
+    (Metal::ToolSteel, "O1", 0.283),
+];
+
+/// Density in lb/in³, by grade where known, else by metal family.
+pub fn density(material: &Material) -> f64 {
+    let grade = material.grade.as_deref().unwrap_or("").to_uppercase();
+    GRADE_DENSITIES
+        .iter()
+        .find(|(metal, prefix, _)| *metal == material.metal && grade.starts_with(prefix))
+        .map(|(_, _, density)| *density)
+        .or_else(|| METAL_DENSITIES.iter().find(|(metal, _)| *metal == material.metal).map(|(_, density)| *density))
+        .expect("every metal has a density")
+}
+
+/// How a product's weight scales with the size it is cut to, in pounds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "basis", content = "lb", rename_all = "snake_case")]
+pub enum WeightRate {
+    /// Bar, tube, structural shapes and pipe, sold by length.
+    PerFoot(f64),
+    /// Sheet and plate, sold by length and width.
+    PerSquareFoot(f64),
+}
+
+impl WeightRate {
+    /// Weight in pounds of one piece. Products sold by area need a width.
+    pub fn weight(&self, length: Length, width: Option<Length>) -> Option<f64> {
+        let feet = length.inches() / 12.0;
+        match *self {
+            WeightRate::PerFoot(rate) => Some(rate * feet),
+            WeightRate::PerSquareFoot(rate) => Some(rate * feet * width?.inches() / 12.0),
+        }
+    }
+}
+
+/// Weight per foot or per square foot, from the product's cross-section and
+/// density. `None` when the description or size can't be parsed, or the
+/// shape's weight doesn't follow from its catalog dimensions (grating,
+/// expanded sheet, diamond grip plank).
+pub fn weight_rate(product: &ProductInfo) -> Option<WeightRate> {
+    let material = product.material()?;
+    let dimensions = product.dimensions()?;
+    let density = density(&material);
+    if let Some(area) = area(&dimensions) {
+        return Some(WeightRate::PerFoot(area * 12.0 * density));
+    }
+    effective_thickness(&dimensions).map(|thickness| WeightRate::PerSquareFoot(thickness * 144.0 * density))
+}
+
+/// Weight in pounds per foot of a product sold by length.
+pub fn weight_per_foot(product: &ProductInfo) -> Option<f64> {
+    match weight_rate(product)? {
+        WeightRate::PerFoot(rate) => Some(rate),
+        WeightRate::PerSquareFoot(_) => None,
+    }
+}
+
+/// Weight in pounds of one piece of `product` cut to `length` (and `width`,
+/// for sheet and plate).
+pub fn piece_weight(product: &ProductInfo, length: Length, width: Option<Length>) -> Option<f64> {
+    weight_rate(product)?.weight(length, width)
+}